@@ -0,0 +1,412 @@
+use crate::{Direction, ProgramState, ProgramStateId, State, Symbol, TapeStorage, TransitionRule, TuringError, TuringMachine};
+#[cfg(test)]
+use crate::StateTarget;
+
+// Layout of the binary encoding, every number is an unsigned LEB128 varint:
+//     magic `TM` and format version
+//     alphabet size, every symbol as a unicode scalar value, index of the blank
+//     nondeterministic flag
+//     number of states, every state id
+//     initial state and current state, both 0 if not set and the value + 1 otherwise
+//     number of rules, every rule as from state, from symbol, new symbol, direction, target
+//     number of wildcard rules, every rule as from state, new symbol (0 writes back the symbol read, index + 1
+//     otherwise), direction, target
+//     number of default rules, every rule as from state, new symbol, direction, target
+//     number of accepting states, every state id
+//     number of labels, every label as state id, length in bytes, UTF-8 bytes
+//     head offset within the tape, number of runs, every run as symbol and length
+// The tape holds the symbols between the outermost non-blank cells and the head, like `to_json`.
+// Everything is sorted, so equal machines encode to the same bytes.
+const MAGIC: &[u8] = b"TM";
+const VERSION: u64 = 1;
+const MAX_TAPE_LEN: usize = 1 << 24; // symbols a decoded tape can hold, bounds what a corrupted run length allocates
+
+// states are numbered `Termination`, `Halt`, `Accept`, `Reject`, followed by the program states
+const PROGRAM_STATE_OFFSET: u64 = 4;
+
+fn state_code(state: State) -> u64 {
+    match state {
+        State::Termination => 0,
+        State::Halt => 1,
+        State::Accept => 2,
+        State::Reject => 3,
+        State::ProgramState(ProgramState { id }) => id as u64 + PROGRAM_STATE_OFFSET,
+    }
+}
+
+fn state_from_code(code: u64) -> Result<State, TuringError> {
+    Ok(match code {
+        0 => State::Termination,
+        1 => State::Halt,
+        2 => State::Accept,
+        3 => State::Reject,
+        code => State::define(id_from_code(code - PROGRAM_STATE_OFFSET)?),
+    })
+}
+
+fn id_from_code(code: u64) -> Result<ProgramStateId, TuringError> {
+    ProgramStateId::try_from(code).map_err(|_| invalid("state id out of range"))
+}
+
+fn invalid(message: &str) -> TuringError {
+    TuringError::InvalidDefinition(message.to_string())
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn uint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    fn state(&mut self, state: State) {
+        self.uint(state_code(state));
+    }
+
+    fn direction(&mut self, direction: Direction) {
+        self.uint(match direction {
+            Direction::Left => 0,
+            Direction::Right => 1,
+            Direction::Stay => 2,
+        });
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn uint(&mut self) -> Result<u64, TuringError> {
+        let mut value = 0u64;
+        for shift in (0..u64::BITS).step_by(7) {
+            let (&byte, rest) = self.bytes.split_first().ok_or_else(|| invalid("unexpected end of input"))?;
+            self.bytes = rest;
+            let bits = (byte & 0x7f) as u64;
+            if (bits << shift) >> shift != bits {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("number does not fit into 64 bits"))
+    }
+
+    fn id(&mut self) -> Result<ProgramStateId, TuringError> {
+        id_from_code(self.uint()?)
+    }
+
+    fn len(&mut self) -> Result<usize, TuringError> {
+        // every counted item takes at least one byte, which bounds allocations on corrupted input
+        let len = self.uint()?;
+        match usize::try_from(len) {
+            Ok(len) if len <= self.bytes.len() => Ok(len),
+            _ => Err(invalid("length exceeds the input")),
+        }
+    }
+
+    fn symbol(&mut self) -> Result<Symbol, TuringError> {
+        u8::try_from(self.uint()?).map(Symbol::new).map_err(|_| invalid("symbol out of range"))
+    }
+
+    fn state(&mut self) -> Result<State, TuringError> {
+        state_from_code(self.uint()?)
+    }
+
+    fn direction(&mut self) -> Result<Direction, TuringError> {
+        match self.uint()? {
+            0 => Ok(Direction::Left),
+            1 => Ok(Direction::Right),
+            2 => Ok(Direction::Stay),
+            _ => Err(invalid("unknown direction")),
+        }
+    }
+}
+
+impl<T: TapeStorage> TuringMachine<T> {
+    /// Encodes the machine definition together with its current tape, head and state into a compact binary form,
+    /// see `from_bytes`. The tape is run-length encoded.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer { bytes: MAGIC.to_vec() };
+        writer.uint(VERSION);
+
+        writer.uint(self.alphabet.len() as u64);
+        self.alphabet.iter().for_each(|&c| writer.uint(c as u64));
+        writer.uint(self.blank.index() as u64);
+        writer.uint(self.nondeterministic as u64);
+
+        let mut states = self.states.keys().copied().collect::<Vec<_>>();
+        states.sort();
+        writer.uint(states.len() as u64);
+        states.iter().for_each(|&id| writer.uint(id as u64));
+        writer.uint(self.initial_state.map_or(0, |id| id as u64 + 1));
+        writer.uint(self.current_state.map_or(0, |state| state_code(state) + 1));
+
+        let mut rules = self.transition_table
+            .values()
+            .flat_map(|rules| rules.values().flatten())
+            .collect::<Vec<_>>();
+        rules.sort_by_key(|rule| (rule.from_state.id, rule.from_symbol.index()));
+        writer.uint(rules.len() as u64);
+        for rule in rules {
+            writer.uint(rule.from_state.id as u64);
+            writer.uint(rule.from_symbol.index() as u64);
+            writer.uint(rule.new_symbol.index() as u64);
+            writer.direction(rule.head_move_dir);
+            writer.state(rule.to_state);
+        }
+
+        let mut wildcard_rules = self.wildcard_rules.iter().collect::<Vec<_>>();
+        wildcard_rules.sort_by_key(|(id, _)| **id);
+        writer.uint(wildcard_rules.len() as u64);
+        for (id, rule) in wildcard_rules {
+            writer.uint(*id as u64);
+            writer.uint(rule.new_symbol.map_or(0, |symbol| symbol.index() as u64 + 1));
+            writer.direction(rule.head_move_dir);
+            writer.state(rule.to_state);
+        }
+        let mut default_rules = self.default_rules.iter().collect::<Vec<_>>();
+        default_rules.sort_by_key(|(id, _)| **id);
+        writer.uint(default_rules.len() as u64);
+        for (id, rule) in default_rules {
+            writer.uint(*id as u64);
+            writer.uint(rule.new_symbol.unwrap().index() as u64); // default rules always write a symbol
+            writer.direction(rule.head_move_dir);
+            writer.state(rule.to_state);
+        }
+
+        let mut accepting_states = self.accepting_states.iter().copied().collect::<Vec<_>>();
+        accepting_states.sort();
+        writer.uint(accepting_states.len() as u64);
+        accepting_states.iter().for_each(|&id| writer.uint(id as u64));
+
+        let mut labels = self.labels.iter().collect::<Vec<_>>();
+        labels.sort();
+        writer.uint(labels.len() as u64);
+        for (id, label) in labels {
+            writer.uint(*id as u64);
+            writer.uint(label.len() as u64);
+            writer.bytes.extend(label.as_bytes());
+        }
+
+        let (start, end) = self.observed_range();
+        let mut runs = Vec::<(Symbol, u64)>::new();
+        for symbol in self.read_tape_range(start, end + 1) {
+            match runs.last_mut() {
+                Some((last, len)) if *last == symbol => *len += 1,
+                _ => runs.push((symbol, 1)),
+            }
+        }
+        writer.uint((self.head - start) as u64);
+        writer.uint(runs.len() as u64);
+        for (symbol, len) in runs {
+            writer.uint(symbol.index() as u64);
+            writer.uint(len);
+        }
+        writer.bytes
+    }
+}
+
+impl TuringMachine {
+    /// Decodes a machine encoded with `to_bytes`, validating it the same way as a hand-built definition.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TuringMachine, TuringError> {
+        let mut reader = Reader {
+            bytes: bytes.strip_prefix(MAGIC).ok_or_else(|| invalid("not an encoded machine"))?,
+        };
+        if reader.uint()? != VERSION {
+            return Err(invalid("unsupported format version"));
+        }
+
+        let alphabet = (0..reader.len()?)
+            .map(|_| {
+                let c = u32::try_from(reader.uint()?).ok().and_then(char::from_u32);
+                c.ok_or_else(|| invalid("invalid alphabet symbol"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let blank = *alphabet.get(reader.uint()? as usize).ok_or_else(|| invalid("blank out of range"))?;
+        let mut machine = TuringMachine::with_alphabet(&alphabet, blank)?;
+        machine.set_nondeterministic(reader.uint()? != 0);
+
+        let states = (0..reader.len()?).map(|_| reader.id().map(|id| ProgramState { id })).collect::<Result<Vec<_>, _>>()?;
+        machine.define_states(&states);
+        let initial_state = match reader.uint()? {
+            0 => None,
+            code => Some(id_from_code(code - 1)?),
+        };
+        let current_state = match reader.uint()? {
+            0 => None,
+            code => Some(state_from_code(code - 1)?),
+        };
+
+        let rules = (0..reader.len()?)
+            .map(|_| Ok(TransitionRule::new(
+                ProgramState { id: reader.id()? },
+                reader.symbol()?,
+                reader.symbol()?,
+                reader.direction()?,
+                reader.state()?,
+            )))
+            .collect::<Result<Vec<_>, TuringError>>()?;
+        machine.define_transition_table(&rules)?;
+        for _ in 0..reader.len()? {
+            let from_state = ProgramState { id: reader.id()? };
+            let new_symbol = match reader.uint()? {
+                0 => None,
+                index => Some(Symbol::new(u8::try_from(index - 1).map_err(|_| invalid("symbol out of range"))?)),
+            };
+            machine.define_wildcard_rule(from_state, new_symbol, reader.direction()?, reader.state()?)?;
+        }
+        for _ in 0..reader.len()? {
+            let from_state = ProgramState { id: reader.id()? };
+            machine.define_default_rule(from_state, reader.symbol()?, reader.direction()?, reader.state()?)?;
+        }
+
+        let accepting_states = (0..reader.len()?).map(|_| reader.id()).collect::<Result<Vec<_>, _>>()?;
+        machine.set_accepting_states(&accepting_states)?;
+        for _ in 0..reader.len()? {
+            let id = reader.id()?;
+            let len = reader.len()?;
+            let (label, rest) = reader.bytes.split_at(len);
+            let label = std::str::from_utf8(label).map_err(|_| invalid("label is not valid UTF-8"))?;
+            machine.labels.insert(id, label.to_string());
+            reader.bytes = rest;
+        }
+
+        let head = reader.uint()?;
+        let runs = (0..reader.len()?).map(|_| Ok((reader.symbol()?, reader.uint()?))).collect::<Result<Vec<_>, TuringError>>()?;
+        if !reader.bytes.is_empty() {
+            return Err(invalid("trailing bytes after the machine"));
+        }
+        // the whole input is read and the tape length checked before the tape is allocated
+        let mut tape_len = 0usize;
+        for &(symbol, len) in &runs {
+            if machine.symbol_char(symbol).is_none() {
+                return Err(TuringError::SymbolNotInAlphabet(symbol));
+            }
+            let end = usize::try_from(len).ok().and_then(|len| tape_len.checked_add(len));
+            tape_len = end.filter(|&end| end <= MAX_TAPE_LEN).ok_or_else(|| invalid("tape is too long"))?;
+        }
+        let head = usize::try_from(head).ok().filter(|&head| head < tape_len || head == 0);
+        let head = head.ok_or_else(|| invalid("head is outside the tape"))?;
+        if let Some(initial_state) = initial_state {
+            machine.set_initial_state(initial_state)?;
+        }
+        let start = machine.head();
+        machine.ensure_tape_len(start + tape_len);
+        let mut position = start;
+        for (symbol, len) in runs {
+            let len = len as usize; // at most `MAX_TAPE_LEN`
+            if symbol != machine.blank {
+                (position..position + len).for_each(|position| machine.set_symbol(position, symbol));
+            }
+            position += len;
+        }
+        machine.set_head(start + head);
+        machine.current_state = current_state;
+
+        Ok(machine)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_machine() -> TuringMachine {
+        let mut machine = TuringMachine::with_alphabet(&['_', 'a', 'b'], '_').unwrap();
+        machine.define_named_states(&[(1, "scan"), (300, "back")]);
+        machine.define_transition_table(&[
+            (1, Symbol::new(1), Symbol::new(2), Direction::Right, StateTarget::To(1)),
+            (1, Symbol::new(0), Symbol::new(0), Direction::Left, StateTarget::To(300)),
+            (300, Symbol::new(2), Symbol::new(1), Direction::Left, StateTarget::To(300)),
+        ]).unwrap();
+        machine.define_wildcard_rule(ProgramState { id: 1 }, None, Direction::Right, State::define(1)).unwrap();
+        machine.define_default_rule(ProgramState { id: 300 }, Symbol::new(0), Direction::Stay, State::Accept).unwrap();
+        machine.set_accepting_states(&[300]).unwrap();
+        machine.set_initial_state(1).unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 1, 2, 2, 2, 2, 1])).unwrap();
+        machine
+    }
+
+    #[test]
+    fn to_bytes_round_trip_test() {
+        let mut machine = example_machine();
+        machine.step().unwrap();
+        let bytes = machine.to_bytes();
+
+        let mut decoded = TuringMachine::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.read_observed(), machine.read_observed());
+        assert_eq!(decoded.current_state(), machine.current_state());
+        assert_eq!(decoded.state_label(300), Some("back"));
+
+        assert_eq!(decoded.run().unwrap(), machine.run().unwrap());
+        assert_eq!(decoded.read_observed(), machine.read_observed());
+        assert_eq!(decoded.to_bytes(), machine.to_bytes());
+    }
+
+    #[test]
+    fn to_bytes_is_compact_test() {
+        let mut machine = TuringMachine::new();
+        machine.define_states(&[ProgramState { id: 1 }]);
+        machine.define_transition_table(&[(1, Symbol::One, Symbol::Zero, Direction::Right, StateTarget::Halt)]).unwrap();
+        machine.set_initial_state(1).unwrap();
+        machine.write_to_tape(&[Symbol::One; 1000]).unwrap();
+
+        let bytes = machine.to_bytes();
+        assert!(bytes.len() < 40, "{} bytes", bytes.len());
+        assert_eq!(TuringMachine::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+    }
+
+    #[test]
+    fn from_bytes_rejects_malformed_input_test() {
+        let bytes = example_machine().to_bytes();
+
+        assert!(matches!(TuringMachine::from_bytes(b"XX\x01"), Err(TuringError::InvalidDefinition(_))));
+        for len in 0..bytes.len() {
+            assert!(TuringMachine::from_bytes(&bytes[..len]).is_err(), "truncated to {} bytes", len);
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(TuringMachine::from_bytes(&trailing), Err(TuringError::InvalidDefinition(_))));
+        let mut version = bytes.clone();
+        version[2] = 2;
+        assert!(matches!(TuringMachine::from_bytes(&version), Err(TuringError::InvalidDefinition(_))));
+        assert!(TuringMachine::from_bytes(&[b'T', b'M', 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]).is_err());
+
+        // the tape ends with the head offset, a single run, its symbol and its length
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&[Symbol::One]).unwrap();
+        let bytes = machine.to_bytes();
+        assert_eq!(bytes[bytes.len() - 4..], [0, 1, 1, 1]);
+        let mut head = bytes.clone();
+        let offset = head.len() - 4;
+        head[offset] = 1;
+        assert_eq!(TuringMachine::from_bytes(&head).err(), Some(invalid("head is outside the tape")));
+        head.splice(offset..offset + 1, [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        assert_eq!(TuringMachine::from_bytes(&head).err(), Some(invalid("head is outside the tape")));
+
+        // run lengths are checked against the tape limit before anything is allocated, also when they add up past it
+        let mut long_run = bytes[..bytes.len() - 1].to_vec();
+        long_run.extend([0x81, 0x80, 0x80, 0x08]); // `MAX_TAPE_LEN + 1`
+        assert_eq!(TuringMachine::from_bytes(&long_run).err(), Some(invalid("tape is too long")));
+        let mut long_runs = bytes[..bytes.len() - 3].to_vec();
+        long_runs.extend([2, 0, 0xff, 0xff, 0xff, 0x07, 1, 0xff, 0xff, 0xff, 0x07]);
+        assert_eq!(TuringMachine::from_bytes(&long_runs).err(), Some(invalid("tape is too long")));
+        let mut max_run = bytes[..bytes.len() - 2].to_vec();
+        max_run.extend([0, 0x80, 0x80, 0x80, 0x08]); // `MAX_TAPE_LEN` blanks
+        assert!(TuringMachine::from_bytes(&max_run).unwrap().tape_len() >= MAX_TAPE_LEN);
+    }
+}
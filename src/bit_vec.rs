@@ -1,13 +1,363 @@
+// Bits of a cell are indexed MSB-first: index 0 is the most significant bit and index `BITS - 1`
+// the least significant one, so that a tape reads left to right like the binary representation of its cells.
+// Indices outside of `0..BITS` are bugs, the `try_` variants reject them instead.
+// The functions work on any unsigned integer cell (`BitWord`), the tape itself uses `usize` cells.
+
+use crate::TuringError;
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
 pub const USIZE_BIT_SIZE: usize = usize::BITS as usize;
 
-pub fn get_bit(cell: &usize, index: &usize) -> usize {
-    (cell >> (USIZE_BIT_SIZE - index - 1)) & 1 
+/// An unsigned integer that can be used as a cell of bits.
+pub trait BitWord:
+    Copy
+    + Eq
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+{
+    const BITS: usize;
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+macro_rules! impl_bit_word {
+    ($($word:ty),*) => {
+        $(impl BitWord for $word {
+            const BITS: usize = <$word>::BITS as usize;
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+impl_bit_word!(u8, u16, u32, u64, usize);
+
+// the single-bit mask of `index`
+fn mask<W: BitWord>(index: usize) -> W {
+    debug_assert!(index < W::BITS, "bit index {} out of range", index);
+    W::ONE << (W::BITS - index - 1)
+}
+
+pub fn get_bit<W: BitWord>(cell: W, index: usize) -> usize {
+    (cell & mask(index) != W::ZERO) as usize
+}
+
+pub fn set_bit<W: BitWord>(cell: &mut W, index: usize) {
+    *cell = *cell | mask(index);
+}
+
+pub fn unset_bit<W: BitWord>(cell: &mut W, index: usize) {
+    *cell = *cell & !mask::<W>(index);
+}
+
+/// Flips a bit with a single XOR, without reading it first. `flip_bit` does the same within a sequence of cells.
+pub fn toggle_bit<W: BitWord>(cell: &mut W, index: usize) {
+    *cell = *cell ^ mask(index);
+}
+
+/// The mask of the `len` bits starting at `start`, which must end within the cell.
+pub fn bit_mask<W: BitWord>(start: usize, len: usize) -> W {
+    debug_assert!(start + len <= W::BITS, "bit range {}..{} out of range", start, start + len);
+    if len == 0 {
+        return W::ZERO;
+    }
+    let below_end = match start + len {
+        end if end == W::BITS => W::ZERO,
+        end => !W::ZERO >> end,
+    };
+    (!W::ZERO >> start) & !below_end
+}
+
+/// Sets the `len` bits starting at `start` at once.
+pub fn set_bits<W: BitWord>(cell: &mut W, start: usize, len: usize) {
+    *cell = *cell | bit_mask(start, len);
+}
+
+/// Clears the `len` bits starting at `start` at once.
+pub fn unset_bits<W: BitWord>(cell: &mut W, start: usize, len: usize) {
+    *cell = *cell & !bit_mask::<W>(start, len);
+}
+
+pub fn try_get_bit<W: BitWord>(cell: W, index: usize) -> Option<usize> {
+    (index < W::BITS).then(|| get_bit(cell, index))
+}
+
+pub fn try_set_bit<W: BitWord>(cell: &mut W, index: usize) -> Result<(), TuringError> {
+    check_index::<W>(index)?;
+    set_bit(cell, index);
+    Ok(())
+}
+
+pub fn try_unset_bit<W: BitWord>(cell: &mut W, index: usize) -> Result<(), TuringError> {
+    check_index::<W>(index)?;
+    unset_bit(cell, index);
+    Ok(())
+}
+
+fn check_index<W: BitWord>(index: usize) -> Result<(), TuringError> {
+    if index >= W::BITS {
+        return Err(TuringError::BitIndexOutOfRange(index));
+    }
+    Ok(())
+}
+
+/// One `usize` worth of tape bits, indexed MSB-first like the functions above.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BitCell(usize);
+
+impl BitCell {
+    pub const ZERO: BitCell = BitCell(0);
+
+    pub fn new(bits: usize) -> BitCell {
+        BitCell(bits)
+    }
+
+    pub fn bits(&self) -> usize {
+        self.0
+    }
+
+    pub fn get(&self, index: usize) -> usize {
+        get_bit(self.0, index)
+    }
+
+    pub fn set(&mut self, index: usize) {
+        set_bit(&mut self.0, index);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        unset_bit(&mut self.0, index);
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        toggle_bit(&mut self.0, index);
+    }
+
+    /// Sets the bit to `1` for any non-zero `bit` and clears it otherwise.
+    pub fn assign(&mut self, index: usize, bit: usize) {
+        match bit {
+            0 => self.clear(index),
+            _ => self.set(index),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The bits of the cell from index 0 to `USIZE_BIT_SIZE - 1`.
+    pub fn iter_bits(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..USIZE_BIT_SIZE).map(|index| self.get(index))
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+impl fmt::Binary for BitCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+/// Reads bit number `bit` of a sequence of cells, counting from the first bit of the first cell.
+pub fn read_bit(cells: &[BitCell], bit: usize) -> usize {
+    cells[bit / USIZE_BIT_SIZE].get(bit % USIZE_BIT_SIZE)
+}
+
+/// Writes bit number `bit` of a sequence of cells, see `read_bit`.
+pub fn write_bit(cells: &mut [BitCell], bit: usize, value: usize) {
+    cells[bit / USIZE_BIT_SIZE].assign(bit % USIZE_BIT_SIZE, value);
 }
 
-pub fn set_bit(cell: &mut usize, index: &usize) {
-    *cell |= 1 << (USIZE_BIT_SIZE - index - 1);
+/// Flips bit number `bit` of a sequence of cells, see `read_bit`.
+pub fn flip_bit(cells: &mut [BitCell], bit: usize) {
+    cells[bit / USIZE_BIT_SIZE].toggle(bit % USIZE_BIT_SIZE);
 }
 
-pub fn unset_bit(cell: &mut usize, index: &usize) {
-    *cell &= !(1 << (USIZE_BIT_SIZE - index - 1))
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bit_order_is_msb_first_test() {
+        let mut cell = 0usize;
+        set_bit(&mut cell, 0);
+        assert_eq!(cell, 1 << (USIZE_BIT_SIZE - 1));
+        assert_eq!(get_bit(cell, 0), 1);
+
+        set_bit(&mut cell, USIZE_BIT_SIZE - 1);
+        assert_eq!(cell, (1 << (USIZE_BIT_SIZE - 1)) | 1);
+        assert_eq!(get_bit(cell, USIZE_BIT_SIZE - 1), 1);
+
+        unset_bit(&mut cell, 0);
+        assert_eq!(cell, 1);
+        assert_eq!(get_bit(cell, 0), 0);
+    }
+
+    #[test]
+    fn set_then_unset_round_trips_every_index_test() {
+        for index in 0..USIZE_BIT_SIZE {
+            let mut cell = 0usize;
+            set_bit(&mut cell, index);
+            assert_eq!(cell.count_ones(), 1);
+            assert_eq!((0..USIZE_BIT_SIZE).map(|i| get_bit(cell, i)).collect::<Vec<_>>(), (0..USIZE_BIT_SIZE).map(|i| (i == index) as usize).collect::<Vec<_>>());
+
+            unset_bit(&mut cell, index);
+            assert_eq!(cell, 0);
+        }
+    }
+
+    #[test]
+    fn bit_functions_on_u8_test() {
+        let mut cell = 0u8;
+        set_bit(&mut cell, 0);
+        set_bit(&mut cell, 7);
+        assert_eq!(cell, 0b1000_0001);
+        assert_eq!((0..8).map(|index| get_bit(cell, index)).collect::<Vec<_>>(), vec![1, 0, 0, 0, 0, 0, 0, 1]);
+
+        toggle_bit(&mut cell, 1);
+        unset_bit(&mut cell, 0);
+        assert_eq!(cell, 0b0100_0001);
+        assert_eq!(try_get_bit(cell, 8), None);
+        assert_eq!(try_set_bit(&mut cell, 8), Err(TuringError::BitIndexOutOfRange(8)));
+        assert_eq!(try_unset_bit(&mut 0u16, 15), Ok(()));
+        assert_eq!(try_unset_bit(&mut 0u16, 16), Err(TuringError::BitIndexOutOfRange(16)));
+    }
+
+    #[test]
+    fn bit_functions_agree_across_word_sizes_test() {
+        // the same byte read through every word size, aligned to its most significant end
+        let byte = 0b1011_0010u8;
+        let bits = (0..8).map(|index| get_bit(byte, index)).collect::<Vec<_>>();
+        assert_eq!((0..8).map(|index| get_bit((byte as u32) << 24, index)).collect::<Vec<_>>(), bits);
+        assert_eq!((0..8).map(|index| get_bit((byte as u64) << 56, index)).collect::<Vec<_>>(), bits);
+        assert_eq!((0..8).map(|index| get_bit((byte as usize) << (USIZE_BIT_SIZE - 8), index)).collect::<Vec<_>>(), bits);
+        assert_eq!(<u8 as BitWord>::BITS, 8);
+        assert_eq!(<usize as BitWord>::BITS, USIZE_BIT_SIZE);
+    }
+
+    #[test]
+    fn bit_functions_match_shifts_test() {
+        let value = 0b1011_0010usize << (USIZE_BIT_SIZE - 8) | 0b110;
+        for index in 0..USIZE_BIT_SIZE {
+            let shift = USIZE_BIT_SIZE - index - 1;
+            assert_eq!(get_bit(value, index), (value >> shift) & 1);
+            assert_eq!(BitCell::new(value).get(index), get_bit(value, index));
+
+            let (mut set, mut unset) = (value, value);
+            set_bit(&mut set, index);
+            unset_bit(&mut unset, index);
+            assert_eq!(set, value | (1 << shift));
+            assert_eq!(unset, value & !(1 << shift));
+        }
+    }
+
+    #[test]
+    fn set_and_unset_bits_test() {
+        let mut cell = 0usize;
+        set_bits(&mut cell, 0, USIZE_BIT_SIZE);
+        assert_eq!(cell, usize::MAX);
+        unset_bits(&mut cell, 0, USIZE_BIT_SIZE);
+        assert_eq!(cell, 0);
+
+        set_bits(&mut cell, 0, 3);
+        assert_eq!(cell, 0b111 << (USIZE_BIT_SIZE - 3));
+        set_bits(&mut cell, USIZE_BIT_SIZE - 2, 2);
+        assert_eq!(cell, (0b111 << (USIZE_BIT_SIZE - 3)) | 0b11);
+        unset_bits(&mut cell, 1, USIZE_BIT_SIZE - 2);
+        assert_eq!(cell, (1 << (USIZE_BIT_SIZE - 1)) | 1);
+        set_bits(&mut cell, 5, 0);
+        assert_eq!(cell, (1 << (USIZE_BIT_SIZE - 1)) | 1);
+
+        let mut byte = 0u8;
+        set_bits(&mut byte, 2, 4);
+        assert_eq!(byte, 0b0011_1100);
+        assert_eq!(bit_mask::<u8>(7, 1), 1);
+        assert_eq!(bit_mask::<u8>(8, 0), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn set_bits_past_cell_end_panics_test() {
+        set_bits(&mut 0u8, 6, 3);
+    }
+
+    #[test]
+    fn toggle_bit_twice_restores_cell_test() {
+        for index in [0, 5, USIZE_BIT_SIZE - 1] {
+            let original = 0b1010_0110usize;
+            let mut cell = original;
+
+            toggle_bit(&mut cell, index);
+            assert_ne!(cell, original);
+            assert_eq!(get_bit(cell, index), 1 - get_bit(original, index));
+            toggle_bit(&mut cell, index);
+            assert_eq!(cell, original);
+        }
+    }
+
+    #[test]
+    fn try_bit_functions_check_range_test() {
+        let mut cell = 0usize;
+        assert_eq!(try_set_bit(&mut cell, USIZE_BIT_SIZE - 1), Ok(()));
+        assert_eq!(try_get_bit(cell, USIZE_BIT_SIZE - 1), Some(1));
+        assert_eq!(try_get_bit(cell, 0), Some(0));
+        assert_eq!(try_unset_bit(&mut cell, USIZE_BIT_SIZE - 1), Ok(()));
+        assert_eq!(cell, 0);
+
+        assert_eq!(try_get_bit(cell, USIZE_BIT_SIZE), None);
+        assert_eq!(try_set_bit(&mut cell, USIZE_BIT_SIZE), Err(TuringError::BitIndexOutOfRange(USIZE_BIT_SIZE)));
+        assert_eq!(try_unset_bit(&mut cell, USIZE_BIT_SIZE + 7), Err(TuringError::BitIndexOutOfRange(USIZE_BIT_SIZE + 7)));
+        assert_eq!(try_get_bit(cell, usize::MAX), None);
+        assert_eq!(cell, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn get_bit_out_of_range_panics_test() {
+        get_bit(0usize, USIZE_BIT_SIZE);
+    }
+
+    #[test]
+    fn bit_cell_test() {
+        let mut cell = BitCell::ZERO;
+        cell.set(0);
+        cell.set(USIZE_BIT_SIZE - 1);
+        cell.assign(3, 1);
+        cell.assign(USIZE_BIT_SIZE - 1, 0);
+
+        assert_eq!(cell, BitCell::new((1 << (USIZE_BIT_SIZE - 1)) | (1 << (USIZE_BIT_SIZE - 4))));
+        assert_eq!(cell.count_ones(), 2);
+        assert_eq!(cell.iter_bits().take(5).collect::<Vec<_>>(), vec![1, 0, 0, 1, 0]);
+        assert_eq!(format!("{:b}", BitCell::new(5)), "101");
+        cell.clear(0);
+        cell.clear(3);
+        assert!(cell.is_zero());
+    }
+
+    #[test]
+    fn read_write_bit_across_cells_test() {
+        let mut cells = vec![BitCell::ZERO; 2];
+        write_bit(&mut cells, USIZE_BIT_SIZE - 1, 1);
+        write_bit(&mut cells, USIZE_BIT_SIZE, 1);
+
+        assert_eq!(cells, vec![BitCell::new(1), BitCell::new(1 << (USIZE_BIT_SIZE - 1))]);
+        assert_eq!(read_bit(&cells, USIZE_BIT_SIZE), 1);
+        assert_eq!(read_bit(&cells, USIZE_BIT_SIZE + 1), 0);
+
+        flip_bit(&mut cells, USIZE_BIT_SIZE - 1);
+        flip_bit(&mut cells, USIZE_BIT_SIZE + 1);
+        assert_eq!(cells, vec![BitCell::ZERO, BitCell::new(0b11 << (USIZE_BIT_SIZE - 2))]);
+        flip_bit(&mut cells, USIZE_BIT_SIZE - 1);
+        flip_bit(&mut cells, USIZE_BIT_SIZE - 1);
+        assert_eq!(cells, vec![BitCell::ZERO, BitCell::new(0b11 << (USIZE_BIT_SIZE - 2))]);
+    }
 }
@@ -0,0 +1,128 @@
+use crate::{Direction, ProgramState, ProgramStateId, State, Symbol, TransitionRule, TuringError, TuringMachine};
+
+/// Chainable alternative to calling `define_states`, `define_transition_table` and `set_initial_state`
+/// by hand, which validates the whole definition at once in `build`.
+#[derive(Default)]
+pub struct TuringMachineBuilder {
+    states: Vec<ProgramState>,
+    initial_state: Option<ProgramStateId>,
+    rules: Vec<TransitionRule>,
+}
+
+impl TuringMachineBuilder {
+    pub fn new() -> TuringMachineBuilder {
+        TuringMachineBuilder::default()
+    }
+
+    pub fn state(mut self, id: ProgramStateId) -> TuringMachineBuilder {
+        self.states.push(ProgramState { id });
+        self
+    }
+
+    pub fn initial(mut self, id: ProgramStateId) -> TuringMachineBuilder {
+        self.initial_state = Some(id);
+        self
+    }
+
+    pub fn rule(
+        mut self,
+        from_state: ProgramStateId,
+        from_symbol: Symbol,
+        new_symbol: Symbol,
+        head_move_dir: Direction,
+        to_state: State,
+    ) -> TuringMachineBuilder {
+        self.rules.push(TransitionRule::new(ProgramState { id: from_state }, from_symbol, new_symbol, head_move_dir, to_state));
+        self
+    }
+
+    /// Builds the machine, failing if the initial state is not set or any rule refers to an undefined state.
+    pub fn build(&self) -> Result<TuringMachine, TuringError> {
+        let initial_state = self.initial_state.ok_or(TuringError::InitialStateNotSet)?;
+
+        let is_defined = |id: &ProgramStateId| self.states.iter().any(|state| state.id == *id);
+        if let Some(id) = self.rules.iter().find_map(|rule| match rule.to_state {
+            State::ProgramState(ProgramState { id }) if !is_defined(&id) => Some(id),
+            _ => None,
+        }) {
+            return Err(TuringError::StateNotDefined(id));
+        }
+
+        let mut machine = TuringMachine::new();
+        machine.define_states(&self.states);
+        machine.define_transition_table(&self.rules)?;
+        machine.set_initial_state(initial_state)?;
+        Ok(machine)
+    }
+}
+
+impl TuringMachine {
+    pub fn builder() -> TuringMachineBuilder {
+        TuringMachineBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the machine from `main.rs`
+    fn example_builder() -> TuringMachineBuilder {
+        TuringMachineBuilder::new()
+            .state(1).state(2).state(3).state(4)
+            .initial(1)
+            .rule(1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination)
+            .rule(1, Symbol::One, Symbol::Zero, Direction::Right, State::define(2))
+            .rule(2, Symbol::Zero, Symbol::One, Direction::Left, State::define(3))
+            .rule(2, Symbol::One, Symbol::One, Direction::Right, State::define(2))
+            .rule(3, Symbol::Zero, Symbol::Zero, Direction::Right, State::define(4))
+            .rule(3, Symbol::One, Symbol::One, Direction::Left, State::define(3))
+            .rule(4, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt)
+            .rule(4, Symbol::One, Symbol::Zero, Direction::Right, State::Termination)
+    }
+
+    #[test]
+    fn build_valid_machine_test() {
+        let mut machine = example_builder().build().unwrap();
+        machine.write_to_tape(&Symbol::vec_from_str("11110111").unwrap()).unwrap();
+
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+        assert_eq!(machine.read_unary_blocks(), vec![6]);
+    }
+
+    #[test]
+    fn builder_shortcut_test() {
+        let mut machine = TuringMachine::builder()
+            .state(1)
+            .initial(1)
+            .rule(1, Symbol::Zero, Symbol::One, Direction::Right, State::Termination)
+            .build()
+            .unwrap();
+
+        assert_eq!(machine.run(), Ok(State::Termination));
+        assert_eq!(machine.read_observed(), vec![Symbol::One]);
+    }
+
+    #[test]
+    fn build_without_initial_state_test() {
+        let result = TuringMachineBuilder::new()
+            .state(1)
+            .rule(1, Symbol::Zero, Symbol::One, Direction::Stay, State::Halt)
+            .build();
+
+        assert_eq!(result.err(), Some(TuringError::InitialStateNotSet));
+    }
+
+    #[test]
+    fn build_with_undefined_states_test() {
+        let undefined_target = example_builder().rule(4, Symbol::One, Symbol::One, Direction::Left, State::define(5)).build();
+        let undefined_source = example_builder().rule(6, Symbol::One, Symbol::One, Direction::Left, State::Halt).build();
+        let undefined_initial = example_builder().initial(7).build();
+        let duplicate_rule = example_builder().rule(1, Symbol::One, Symbol::One, Direction::Left, State::Halt).build();
+
+        assert_eq!(undefined_target.err(), Some(TuringError::StateNotDefined(5)));
+        assert_eq!(undefined_source.err(), Some(TuringError::StateNotDefined(6)));
+        assert_eq!(undefined_initial.err(), Some(TuringError::StateNotDefined(7)));
+        assert_eq!(duplicate_rule.err(), Some(TuringError::DuplicateRule { state: 1, label: None, symbol: Symbol::One }));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{ProgramStateId, State, Symbol, TapeStorage, TuringMachine};
+use std::collections::BTreeMap;
+
+const WILDCARD: &str = "*";
+const DEFAULT: &str = "else";
+const START_NODE: &str = "start";
+
+// node names of the states, the special states get a node of their own which is only drawn when a rule targets it
+fn node(state: State) -> String {
+    match state {
+        State::ProgramState(state) => format!("q{}", state.id),
+        State::Halt => "halt".to_string(),
+        State::Termination => "term".to_string(),
+        State::Accept => "accept".to_string(),
+        State::Reject => "reject".to_string(),
+    }
+}
+
+impl<T: TapeStorage> TuringMachine<T> {
+    /// Renders the state diagram as a Graphviz DOT digraph, e.g. for `dot -Tpng`. Every rule becomes an edge
+    /// labeled `read/write,dir`, rules between the same states share an edge with one label line per rule.
+    /// Accepting program states and the `Halt` and `Accept` states are double-circled, `Termination` and `Reject`
+    /// are drawn as boxes. Wildcard rules read `*` (and write `*` when writing back the symbol read), default rules
+    /// read `else`.
+    pub fn to_dot(&self) -> String {
+        let symbol = |symbol: Symbol| self.symbol_char(symbol).map_or(symbol.to_string(), |c| c.to_string());
+
+        // (from state, to node) -> (order within the state, label) of the rules drawn on that edge
+        let mut edges = BTreeMap::<(ProgramStateId, String), Vec<((usize, usize), String)>>::new();
+        for rule in self.transition_table.values().flat_map(|rules| rules.values().flatten()) {
+            let label = format!("{}/{},{}", symbol(rule.from_symbol), symbol(rule.new_symbol), rule.head_move_dir);
+            let order = (rule.from_symbol.index() as usize, 0);
+            edges.entry((rule.from_state.id, node(rule.to_state))).or_default().push((order, label));
+        }
+        for (order, pattern, rules) in [(1, WILDCARD, &self.wildcard_rules), (2, DEFAULT, &self.default_rules)] {
+            for (id, rule) in rules {
+                let new_symbol = rule.new_symbol.map_or(WILDCARD.to_string(), symbol);
+                let label = format!("{}/{},{}", pattern, new_symbol, rule.head_move_dir);
+                edges.entry((*id, node(rule.to_state))).or_default().push(((usize::MAX, order), label));
+            }
+        }
+
+        let mut states = self.states.keys().copied().collect::<Vec<_>>();
+        states.sort();
+        let mut dot = "digraph turing_machine {\n    rankdir=LR;\n".to_string();
+        for id in &states {
+            let shape = if self.accepting_states.contains(id) { "doublecircle" } else { "circle" };
+            dot += &format!("    q{} [shape={}, label=\"{}\"];\n", id, shape, self.format_state(*id).replace('"', "\\\""));
+        }
+        for (state, shape) in [
+            (State::Halt, "doublecircle"),
+            (State::Accept, "doublecircle"),
+            (State::Termination, "box"),
+            (State::Reject, "box"),
+        ] {
+            if edges.keys().any(|(_, to)| *to == node(state)) {
+                dot += &format!("    {} [shape={}];\n", node(state), shape);
+            }
+        }
+        if let Some(initial_state) = self.initial_state {
+            dot += &format!("    {} [shape=point];\n    {} -> q{};\n", START_NODE, START_NODE, initial_state);
+        }
+        for ((from, to), mut labels) in edges {
+            labels.sort();
+            let label = labels.into_iter().map(|(_, label)| label).collect::<Vec<_>>().join("\\n");
+            dot += &format!("    q{} -> {} [label=\"{}\"];\n", from, to, label);
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Direction, ProgramState};
+
+    #[test]
+    fn to_dot_test() {
+        let mut machine = TuringMachine::from_program("
+            start: q1
+            q1 1 -> 0 R q2
+            q1 0 -> 0 S term
+            q2 1 -> 1 R q2
+            q2 * -> 1 L halt
+        ").unwrap();
+        machine.define_named_states(&[(3, "done")]);
+        machine.define_default_rule(ProgramState { id: 3 }, Symbol::One, Direction::Stay, State::Accept).unwrap();
+        machine.set_accepting_states(&[3]).unwrap();
+
+        assert_eq!(machine.to_dot(), "\
+digraph turing_machine {
+    rankdir=LR;
+    q1 [shape=circle, label=\"q1\"];
+    q2 [shape=circle, label=\"q2\"];
+    q3 [shape=doublecircle, label=\"q_done\"];
+    halt [shape=doublecircle];
+    accept [shape=doublecircle];
+    term [shape=box];
+    start [shape=point];
+    start -> q1;
+    q1 -> q2 [label=\"1/0,R\"];
+    q1 -> term [label=\"0/0,S\"];
+    q2 -> halt [label=\"*/1,L\"];
+    q2 -> q2 [label=\"1/1,R\"];
+    q3 -> accept [label=\"else/1,S\"];
+}
+");
+    }
+
+    #[test]
+    fn to_dot_merges_parallel_edges_test() {
+        let machine = TuringMachine::from_program("q1 0 -> 1 R q1\nq1 1 -> 0 R q1\nq1 * -> * R q1").unwrap();
+
+        assert!(machine.to_dot().contains("    q1 -> q1 [label=\"0/1,R\\n1/0,R\\n*/*,R\"];\n"));
+        assert!(!machine.to_dot().contains("halt"));
+    }
+}
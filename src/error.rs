@@ -0,0 +1,102 @@
+use crate::{ProgramStateId, Symbol};
+use std::fmt;
+
+/// Everything that can go wrong while defining, loading or running a machine.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TuringError {
+    InitialStateNotSet,
+    StateNotDefined(ProgramStateId),
+    DuplicateRule { state: ProgramStateId, label: Option<String>, symbol: Symbol },
+    DuplicateMultiTapeRule { state: ProgramStateId, symbols: Vec<Symbol> }, // the symbols read from every tape
+    MissingRule { state: ProgramStateId, symbol: Symbol },
+    StepLimitExceeded(u64),
+    LoopDetected(u64),
+    TapeCountMismatch { expected: usize, found: usize },
+    AlphabetSize { max: usize },
+    DuplicateSymbol(char),
+    BlankNotInAlphabet(char),
+    UnknownSymbol(char),          // a character that is not part of the machine's alphabet
+    InvalidSymbol(char),          // a character that is not a base-36 digit
+    SymbolNotInAlphabet(Symbol),  // a symbol index beyond the machine's alphabet
+    NotBinary(Symbol),
+    UintTooWide(usize),
+    ValueTooLarge { value: u64, len: usize },
+    BitIndexOutOfRange(usize),
+    NoStepToUndo,
+    ReplayDiverged(u64),          // the step at which replaying the run for `step_back` halted
+    Parse { line: usize, message: String },
+    AtLine { line: usize, error: Box<TuringError> }, // an error of a program line found after parsing it
+    EmptyProgram,
+    InvalidDefinition(String),
+    Io(String),
+}
+
+impl fmt::Display for TuringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuringError::InitialStateNotSet => write!(f, "ERROR: initial state is not set"),
+            TuringError::StateNotDefined(id) => write!(f, "ERROR: state with id `{}` is not defined", id),
+            TuringError::DuplicateRule { state, label: None, symbol } => write!(
+                f,
+                "ERROR: State with id `{}` is already bound to a transition rule for symbol `{}` as a `from_state`",
+                state, symbol,
+            ),
+            TuringError::DuplicateRule { state, label: Some(label), symbol } => write!(
+                f,
+                "ERROR: State `{}` (id `{}`) is already bound to a transition rule for symbol `{}` as a `from_state`",
+                label, state, symbol,
+            ),
+            TuringError::DuplicateMultiTapeRule { state, symbols } => {
+                let symbols = symbols.iter().map(Symbol::to_string).collect::<Vec<_>>().join(", ");
+                write!(
+                    f,
+                    "ERROR: State with id `{}` is already bound to a transition rule for symbols `({})` as a `from_state`",
+                    state, symbols,
+                )
+            }
+            TuringError::MissingRule { state, symbol } => {
+                write!(f, "ERROR: State with id `{}` has no transition rule for symbol `{}`", state, symbol)
+            }
+            TuringError::StepLimitExceeded(steps) => {
+                write!(f, "ERROR: step limit exceeded, machine is still running after {} steps", steps)
+            }
+            TuringError::LoopDetected(steps) => {
+                write!(f, "ERROR: loop detected, machine re-entered a configuration after {} steps", steps)
+            }
+            TuringError::TapeCountMismatch { expected, found } => {
+                write!(f, "ERROR: expected one symbol and direction per tape ({} tapes), found {}", expected, found)
+            }
+            TuringError::AlphabetSize { max } => write!(f, "ERROR: alphabet size should be between 1 and {}", max),
+            TuringError::DuplicateSymbol(c) => write!(f, "ERROR: symbol `{}` appears in the alphabet more than once", c),
+            TuringError::BlankNotInAlphabet(c) => write!(f, "ERROR: blank symbol `{}` is not part of the alphabet", c),
+            TuringError::UnknownSymbol(c) => write!(f, "ERROR: symbol `{}` is not part of the alphabet", c),
+            TuringError::InvalidSymbol(c) => write!(f, "ERROR: `{}` is not a valid symbol, expected a digit", c),
+            TuringError::SymbolNotInAlphabet(symbol) => write!(f, "ERROR: Symbol `{}` is not part of the alphabet", symbol),
+            TuringError::NotBinary(symbol) => write!(f, "ERROR: symbol `{}` is not a binary digit", symbol),
+            TuringError::UintTooWide(len) => write!(f, "ERROR: {} bits do not fit into a {}-bit integer", len, u64::BITS),
+            TuringError::ValueTooLarge { value, len } => write!(f, "ERROR: value `{}` does not fit into {} bits", value, len),
+            TuringError::BitIndexOutOfRange(index) => write!(f, "ERROR: bit index `{}` is out of range of the cell", index),
+            TuringError::NoStepToUndo => write!(f, "ERROR: the machine has not made any step to undo"),
+            TuringError::ReplayDiverged(steps) => {
+                write!(f, "ERROR: cannot undo the step, replaying the run halted after {} steps", steps)
+            }
+            TuringError::Parse { line, message } => write!(f, "ERROR: line {}: {}", line, message),
+            TuringError::AtLine { line, error } => {
+                let message = error.to_string();
+                write!(f, "ERROR: line {}: {}", line, message.strip_prefix("ERROR: ").unwrap_or(&message))
+            }
+            TuringError::EmptyProgram => write!(f, "ERROR: program has no rules and no `start:` directive"),
+            TuringError::InvalidDefinition(message) => write!(f, "ERROR: invalid machine definition: {}", message),
+            TuringError::Io(message) => write!(f, "ERROR: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TuringError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TuringError::AtLine { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
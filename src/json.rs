@@ -0,0 +1,536 @@
+use crate::{DEFAULT_ALPHABET, Direction, ProgramState, ProgramStateId, State, Symbol, TransitionRule, TuringError, TuringMachine};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+#[cfg(feature = "json")]
+use std::fs;
+#[cfg(feature = "json")]
+use std::path::Path;
+
+// JSON schema of a machine definition:
+// {
+//     "alphabet": ["0", "1"],      (optional, defaults to the binary alphabet)
+//     "blank": "0",                (optional, defaults to the first symbol of the alphabet)
+//     "states": [1, 2],
+//     "initial_state": 1,
+//     "transitions": [
+//         { "from_state": 1, "from_symbol": "1", "new_symbol": "0", "direction": "R", "to_state": 2 },
+//         { "from_state": 2, "from_symbol": "0", "new_symbol": "0", "direction": "S", "to_state": "halt" }
+//     ],
+//     "wildcard_transitions": [    (optional, rules for every symbol without a rule, `new_symbol` defaults to the one read)
+//         { "from_state": 2, "direction": "R", "to_state": 2 }
+//     ],
+//     "default_transitions": [     (optional, rules applied when neither an explicit nor a wildcard rule does)
+//         { "from_state": 2, "new_symbol": "0", "direction": "L", "to_state": 1 }
+//     ],
+//     "tape": "0110",              (optional, written to the tape starting at the head)
+//     "head": 2,                   (optional, puts the head this many cells right of the tape start, within the tape)
+//     "current_state": 2,          (optional, defaults to the initial state)
+//     "steps": 12,                 (optional, transitions applied so far, see `TuringMachine::steps`)
+//     "halted_in": 2,              (optional, the state that had no rule for the symbol read, needs a `halt` current state)
+//     "nondeterministic": false,   (optional, allows several rules for the same state and symbol)
+//     "labels": { "1": "scan" },   (optional, names of states)
+//     "accepting_states": [2]      (optional, see `TuringMachine::accepts`)
+// }
+// The same definition backs the `serde` implementations of `TuringMachine`, so other serde formats use this layout too.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MachineDefinition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alphabet: Option<Vec<char>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blank: Option<char>,
+    states: Vec<ProgramStateId>,
+    initial_state: ProgramStateId,
+    transitions: Vec<RuleDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    wildcard_transitions: Vec<WildcardRuleDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    default_transitions: Vec<DefaultRuleDefinition>,
+    #[serde(default)]
+    tape: String,
+    #[serde(default)]
+    head: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_state: Option<TargetDefinition>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    steps: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    halted_in: Option<ProgramStateId>,
+    #[serde(default)]
+    nondeterministic: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    labels: BTreeMap<ProgramStateId, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    accepting_states: Vec<ProgramStateId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleDefinition {
+    from_state: ProgramStateId,
+    from_symbol: char,
+    new_symbol: char,
+    direction: DirectionDefinition,
+    to_state: TargetDefinition,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WildcardRuleDefinition {
+    from_state: ProgramStateId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    new_symbol: Option<char>,
+    direction: DirectionDefinition,
+    to_state: TargetDefinition,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DefaultRuleDefinition {
+    from_state: ProgramStateId,
+    new_symbol: char,
+    direction: DirectionDefinition,
+    to_state: TargetDefinition,
+}
+
+#[derive(Serialize, Deserialize)]
+enum DirectionDefinition {
+    L,
+    R,
+    S,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum TargetDefinition {
+    State(ProgramStateId),
+    Stop(StopDefinition),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StopDefinition {
+    Halt,
+    Term,
+    Accept,
+    Reject,
+}
+
+impl From<&DirectionDefinition> for Direction {
+    fn from(direction: &DirectionDefinition) -> Direction {
+        match direction {
+            DirectionDefinition::L => Direction::Left,
+            DirectionDefinition::R => Direction::Right,
+            DirectionDefinition::S => Direction::Stay,
+        }
+    }
+}
+
+impl From<Direction> for DirectionDefinition {
+    fn from(direction: Direction) -> DirectionDefinition {
+        match direction {
+            Direction::Left => DirectionDefinition::L,
+            Direction::Right => DirectionDefinition::R,
+            Direction::Stay => DirectionDefinition::S,
+        }
+    }
+}
+
+impl From<State> for TargetDefinition {
+    fn from(state: State) -> TargetDefinition {
+        match state {
+            State::ProgramState(ProgramState { id }) => TargetDefinition::State(id),
+            State::Halt => TargetDefinition::Stop(StopDefinition::Halt),
+            State::Termination => TargetDefinition::Stop(StopDefinition::Term),
+            State::Accept => TargetDefinition::Stop(StopDefinition::Accept),
+            State::Reject => TargetDefinition::Stop(StopDefinition::Reject),
+        }
+    }
+}
+
+impl From<&TargetDefinition> for State {
+    fn from(target: &TargetDefinition) -> State {
+        match target {
+            TargetDefinition::State(id) => State::define(*id),
+            TargetDefinition::Stop(StopDefinition::Halt) => State::Halt,
+            TargetDefinition::Stop(StopDefinition::Term) => State::Termination,
+            TargetDefinition::Stop(StopDefinition::Accept) => State::Accept,
+            TargetDefinition::Stop(StopDefinition::Reject) => State::Reject,
+        }
+    }
+}
+
+fn is_zero(steps: &u64) -> bool {
+    *steps == 0
+}
+
+impl TuringMachine {
+    /// Loads a machine definition from a JSON file, see `from_json_str` for the format.
+    #[cfg(feature = "json")]
+    pub fn from_json(path: &Path) -> Result<TuringMachine, TuringError> {
+        let json = fs::read_to_string(path)
+            .map_err(|err| TuringError::Io(format!("failed to read `{}`: {}", path.display(), err)))?;
+        TuringMachine::from_json_str(&json)
+    }
+
+    /// Builds a machine from a JSON definition of its alphabet, states, initial state, transition rules and
+    /// initial tape contents. Directions are written as `"L"`, `"R"` or `"S"` and the special target states
+    /// as `"halt"`, `"term"`, `"accept"` and `"reject"`.
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> Result<TuringMachine, TuringError> {
+        let definition: MachineDefinition = serde_json::from_str(json)
+            .map_err(|err| TuringError::InvalidDefinition(err.to_string()))?;
+        TuringMachine::from_definition(definition)
+    }
+
+    fn from_definition(definition: MachineDefinition) -> Result<TuringMachine, TuringError> {
+        let alphabet = definition.alphabet.unwrap_or(DEFAULT_ALPHABET.to_vec());
+        let blank = definition.blank.or(alphabet.first().copied()).unwrap_or_default();
+        let mut machine = TuringMachine::with_alphabet(&alphabet, blank)?;
+        machine.set_nondeterministic(definition.nondeterministic);
+
+        let states = definition.states.iter().map(|&id| ProgramState { id }).collect::<Vec<_>>();
+        machine.define_states(&states);
+
+        let rules = definition.transitions
+            .iter()
+            .map(|rule| Ok(TransitionRule::new(
+                ProgramState { id: rule.from_state },
+                machine.symbol(rule.from_symbol)?,
+                machine.symbol(rule.new_symbol)?,
+                Direction::from(&rule.direction),
+                State::from(&rule.to_state),
+            )))
+            .collect::<Result<Vec<_>, TuringError>>()?;
+        machine.define_transition_table(&rules)?;
+        for rule in &definition.wildcard_transitions {
+            machine.define_wildcard_rule(
+                ProgramState { id: rule.from_state },
+                rule.new_symbol.map(|c| machine.symbol(c)).transpose()?,
+                Direction::from(&rule.direction),
+                State::from(&rule.to_state),
+            )?;
+        }
+        for rule in &definition.default_transitions {
+            machine.define_default_rule(
+                ProgramState { id: rule.from_state },
+                machine.symbol(rule.new_symbol)?,
+                Direction::from(&rule.direction),
+                State::from(&rule.to_state),
+            )?;
+        }
+        machine.set_initial_state(definition.initial_state)?;
+
+        let tape = definition.tape
+            .chars()
+            .map(|c| machine.symbol(c))
+            .collect::<Result<Vec<Symbol>, TuringError>>()?;
+        if definition.head >= tape.len() && definition.head != 0 {
+            return Err(TuringError::InvalidDefinition(format!("head `{}` is outside the tape", definition.head)));
+        }
+        machine.write_to_tape(&tape)?;
+        machine.set_head(machine.head() + definition.head);
+        if let Some(current_state) = &definition.current_state {
+            machine.current_state = Some(State::from(current_state));
+        }
+        if let Some(id) = definition.halted_in {
+            if !machine.states.contains_key(&id) {
+                return Err(TuringError::StateNotDefined(id));
+            }
+            if machine.current_state != Some(State::Halt) {
+                return Err(TuringError::InvalidDefinition("`halted_in` needs the `halt` current state".to_string()));
+            }
+        }
+        (machine.steps, machine.halted_in) = (definition.steps, definition.halted_in);
+        machine.labels.extend(definition.labels);
+        machine.set_accepting_states(&definition.accepting_states)?;
+
+        Ok(machine)
+    }
+
+    /// Serializes the machine definition together with its current tape, head, state and step count into the
+    /// `from_json_str` format, so a machine that halted without a rule reloads with the same `outcome`. Only the
+    /// part of the tape between the outermost non-blank cells and the head is written, as a string of symbols.
+    /// The history and the run start `step_back` replays from are not kept.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, TuringError> {
+        serde_json::to_string_pretty(&self.to_definition()?)
+            .map_err(|err| TuringError::Io(format!("failed to serialize the machine: {}", err)))
+    }
+
+    fn to_definition(&self) -> Result<MachineDefinition, TuringError> {
+        let initial_state = self.initial_state.ok_or(TuringError::InitialStateNotSet)?;
+
+        let mut states = self.states.keys().copied().collect::<Vec<_>>();
+        states.sort();
+        let mut rules = self.transition_table
+            .values()
+            .flat_map(|rules| rules.values().flatten())
+            .collect::<Vec<_>>();
+        rules.sort_by_key(|rule| (rule.from_state.id, rule.from_symbol.index()));
+        let transitions = rules
+            .into_iter()
+            .map(|rule| RuleDefinition {
+                from_state: rule.from_state.id,
+                from_symbol: self.symbol_char(rule.from_symbol).unwrap(),
+                new_symbol: self.symbol_char(rule.new_symbol).unwrap(),
+                direction: DirectionDefinition::from(rule.head_move_dir),
+                to_state: TargetDefinition::from(rule.to_state),
+            })
+            .collect();
+        let mut wildcard_transitions = self.wildcard_rules
+            .iter()
+            .map(|(id, rule)| WildcardRuleDefinition {
+                from_state: *id,
+                new_symbol: rule.new_symbol.map(|symbol| self.symbol_char(symbol).unwrap()),
+                direction: DirectionDefinition::from(rule.head_move_dir),
+                to_state: TargetDefinition::from(rule.to_state),
+            })
+            .collect::<Vec<_>>();
+        wildcard_transitions.sort_by_key(|rule| rule.from_state);
+        let mut default_transitions = self.default_rules
+            .iter()
+            .map(|(id, rule)| DefaultRuleDefinition {
+                from_state: *id,
+                new_symbol: self.symbol_char(rule.new_symbol.unwrap()).unwrap(),
+                direction: DirectionDefinition::from(rule.head_move_dir),
+                to_state: TargetDefinition::from(rule.to_state),
+            })
+            .collect::<Vec<_>>();
+        default_transitions.sort_by_key(|rule| rule.from_state);
+
+        let (start, end) = self.observed_range();
+        let tape = (start..=end)
+            .map(|position| self.symbol_char(self.get_symbol(position)).unwrap())
+            .collect();
+
+        let mut accepting_states = self.accepting_states.iter().copied().collect::<Vec<_>>();
+        accepting_states.sort();
+
+        let is_binary = self.alphabet == DEFAULT_ALPHABET && self.blank == Symbol::Zero;
+        Ok(MachineDefinition {
+            alphabet: (!is_binary).then(|| self.alphabet.clone()),
+            blank: (!is_binary).then(|| self.symbol_char(self.blank).unwrap()),
+            states,
+            initial_state,
+            transitions,
+            wildcard_transitions,
+            default_transitions,
+            tape,
+            head: self.head - start,
+            current_state: self.current_state.map(TargetDefinition::from),
+            steps: self.steps,
+            halted_in: self.halted_in,
+            nondeterministic: self.nondeterministic,
+            labels: self.labels.iter().map(|(id, label)| (*id, label.clone())).collect(),
+            accepting_states,
+        })
+    }
+}
+
+impl Serialize for TuringMachine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_definition().map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TuringMachine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TuringMachine, D::Error> {
+        TuringMachine::from_definition(MachineDefinition::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod test {
+    use super::*;
+    use crate::Outcome;
+
+    // the machine from `main.rs`
+    const EXAMPLE_JSON: &str = r#"{
+        "states": [1, 2, 3, 4],
+        "initial_state": 1,
+        "transitions": [
+            { "from_state": 1, "from_symbol": "0", "new_symbol": "0", "direction": "S", "to_state": "term" },
+            { "from_state": 1, "from_symbol": "1", "new_symbol": "0", "direction": "R", "to_state": 2 },
+            { "from_state": 2, "from_symbol": "0", "new_symbol": "1", "direction": "L", "to_state": 3 },
+            { "from_state": 2, "from_symbol": "1", "new_symbol": "1", "direction": "R", "to_state": 2 },
+            { "from_state": 3, "from_symbol": "0", "new_symbol": "0", "direction": "R", "to_state": 4 },
+            { "from_state": 3, "from_symbol": "1", "new_symbol": "1", "direction": "L", "to_state": 3 },
+            { "from_state": 4, "from_symbol": "0", "new_symbol": "0", "direction": "S", "to_state": "halt" },
+            { "from_state": 4, "from_symbol": "1", "new_symbol": "0", "direction": "R", "to_state": "term" }
+        ],
+        "tape": "11110111"
+    }"#;
+
+    #[test]
+    fn from_json_str_runs_example_test() {
+        let mut machine = TuringMachine::from_json_str(EXAMPLE_JSON).unwrap();
+        let start = machine.head();
+
+        let report = machine.run_reporting().unwrap();
+
+        assert!(matches!(report.final_state, State::Termination));
+        assert_eq!(report.steps, 10);
+        assert_eq!(report.head_position, start + 2);
+    }
+
+    #[test]
+    fn from_json_reads_file_test() {
+        let path = std::env::temp_dir().join(format!("turing-machine-{}.json", std::process::id()));
+        fs::write(&path, EXAMPLE_JSON).unwrap();
+
+        let machine = TuringMachine::from_json(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(machine.is_ok());
+        assert!(matches!(TuringMachine::from_json(&path), Err(TuringError::Io(_))));
+    }
+
+    #[test]
+    fn from_json_str_with_alphabet_test() {
+        let machine = TuringMachine::from_json_str(r#"{
+            "alphabet": ["_", "a"],
+            "blank": "_",
+            "states": [1],
+            "initial_state": 1,
+            "transitions": [
+                { "from_state": 1, "from_symbol": "a", "new_symbol": "_", "direction": "R", "to_state": 1 }
+            ],
+            "tape": "aa"
+        }"#).unwrap();
+
+        assert!(machine.get_head_value() == machine.symbol('a').unwrap());
+        assert!(machine.blank() == machine.symbol('_').unwrap());
+    }
+
+    #[test]
+    fn from_json_str_rejects_invalid_definitions_test() {
+        let invalid_direction = EXAMPLE_JSON.replace(r#""direction": "S""#, r#""direction": "X""#);
+        let invalid_symbol = EXAMPLE_JSON.replace(r#""new_symbol": "1""#, r#""new_symbol": "2""#);
+        let invalid_initial_state = EXAMPLE_JSON.replace(r#""initial_state": 1"#, r#""initial_state": 5"#);
+
+        assert!(matches!(TuringMachine::from_json_str(&invalid_direction), Err(TuringError::InvalidDefinition(_))));
+        assert_eq!(TuringMachine::from_json_str(&invalid_symbol).err(), Some(TuringError::UnknownSymbol('2')));
+        assert_eq!(TuringMachine::from_json_str(&invalid_initial_state).err(), Some(TuringError::StateNotDefined(5)));
+        assert!(matches!(TuringMachine::from_json_str("{}"), Err(TuringError::InvalidDefinition(_))));
+
+        // the head has to be on the tape, or at its start if the tape is empty
+        let with_head = |head: &str| EXAMPLE_JSON.replace(r#""tape": "11110111""#, &format!(r#""tape": "11110111", "head": {}"#, head));
+        let machine = TuringMachine::from_json_str(&with_head("7")).unwrap();
+        assert_eq!((machine.position(), machine.space_used()), (7, 1));
+        assert_eq!(machine.get_head_value(), Symbol::One);
+        for head in ["8", "1000000000000"] {
+            assert_eq!(
+                TuringMachine::from_json_str(&with_head(head)).err(),
+                Some(TuringError::InvalidDefinition(format!("head `{}` is outside the tape", head))),
+            );
+        }
+        let empty_tape = EXAMPLE_JSON.replace(r#""tape": "11110111""#, r#""head": 0"#);
+        assert_eq!(TuringMachine::from_json_str(&empty_tape).unwrap().position(), 0);
+    }
+
+    #[test]
+    fn to_json_round_trip_test() {
+        let mut machine = TuringMachine::from_json_str(EXAMPLE_JSON).unwrap();
+        for _ in 0..4 {
+            machine.step().unwrap();
+        }
+
+        let json = machine.to_json().unwrap();
+        let mut reloaded = TuringMachine::from_json_str(&json).unwrap();
+
+        assert_eq!(reloaded.to_json().unwrap(), json);
+        let (final_state, trace) = machine.run_traced().unwrap();
+        let (reloaded_final_state, reloaded_trace) = reloaded.run_traced().unwrap();
+        assert!(matches!((final_state, reloaded_final_state), (State::Termination, State::Termination)));
+        assert_eq!(
+            trace.iter().map(|step| (step.state_id, step.read, step.written, step.direction)).collect::<Vec<_>>(),
+            reloaded_trace.iter().map(|step| (step.state_id, step.read, step.written, step.direction)).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn to_json_keeps_run_statistics_test() {
+        let mut machine = TuringMachine::from_json_str(r#"{
+            "states": [1],
+            "initial_state": 1,
+            "transitions": [{ "from_state": 1, "from_symbol": "1", "new_symbol": "0", "direction": "R", "to_state": 1 }],
+            "tape": "11"
+        }"#).unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.outcome(), Some(Outcome::HaltedNoRule));
+
+        let reloaded = TuringMachine::from_json_str(&machine.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.outcome(), Some(Outcome::HaltedNoRule));
+        assert_eq!(reloaded.steps(), 2);
+
+        let running = TuringMachine::from_json_str(EXAMPLE_JSON).unwrap().to_json().unwrap();
+        assert!(!running.contains("steps") && !running.contains("halted_in"));
+        let invalid = running.replace(r#""tape""#, r#""halted_in": 1, "tape""#);
+        assert!(matches!(TuringMachine::from_json_str(&invalid), Err(TuringError::InvalidDefinition(_))));
+    }
+
+    #[test]
+    fn to_json_keeps_alphabet_test() {
+        let machine = TuringMachine::from_json_str(r#"{
+            "alphabet": ["a", "_"],
+            "blank": "_",
+            "states": [1],
+            "initial_state": 1,
+            "transitions": [],
+            "tape": "_a"
+        }"#).unwrap();
+
+        let json = machine.to_json().unwrap();
+        let reloaded = TuringMachine::from_json_str(&json).unwrap();
+
+        assert!(json.contains(r#""tape": "_a""#));
+        assert!(json.contains(r#""head": 0"#));
+        assert_eq!(reloaded.alphabet(), machine.alphabet());
+        assert!(reloaded.blank() == machine.blank());
+    }
+
+    #[test]
+    fn serde_round_trip_test() {
+        let mut machine = TuringMachine::from_json_str(EXAMPLE_JSON).unwrap();
+        machine.define_named_states(&[(1, "start")]);
+        machine.set_accepting_states(&[4, 2]).unwrap();
+
+        let json = serde_json::to_string(&machine).unwrap();
+        let mut reloaded = serde_json::from_str::<TuringMachine>(&json).unwrap();
+
+        assert!(json.contains(r#""tape":"11110111""#));
+        assert!(json.contains(r#""accepting_states":[2,4]"#));
+        assert_eq!(reloaded.state_label(1), Some("start"));
+        assert!(matches!(reloaded.run(), Ok(State::Termination)));
+        assert_eq!(reloaded.read_unary_blocks(), vec![6]);
+
+        let rule = TransitionRule::new(ProgramState { id: 1 }, Symbol::One, Symbol::Zero, Direction::Left, State::Halt);
+        let rule = serde_json::from_str::<TransitionRule>(&serde_json::to_string(&rule).unwrap()).unwrap();
+        assert_eq!(rule.new_symbol, Symbol::Zero);
+        assert!(matches!((rule.head_move_dir, rule.to_state), (Direction::Left, State::Halt)));
+        assert!(serde_json::from_str::<TuringMachine>(r#"{"states": [], "initial_state": 1, "transitions": []}"#).is_err());
+    }
+
+    #[test]
+    fn fallback_transitions_round_trip_test() {
+        let json = r#"{
+            "states": [1, 2],
+            "initial_state": 1,
+            "transitions": [{ "from_state": 1, "from_symbol": "0", "new_symbol": "1", "direction": "S", "to_state": 2 }],
+            "wildcard_transitions": [
+                { "from_state": 1, "direction": "R", "to_state": 1 },
+                { "from_state": 2, "new_symbol": "0", "direction": "S", "to_state": "halt" }
+            ],
+            "default_transitions": [{ "from_state": 2, "new_symbol": "1", "direction": "L", "to_state": "term" }],
+            "tape": "11"
+        }"#;
+        let machine = TuringMachine::from_json_str(json).unwrap();
+
+        let mut reloaded = TuringMachine::from_json_str(&machine.to_json().unwrap()).unwrap();
+
+        assert_eq!(reloaded.to_spec().unwrap(), machine.to_spec().unwrap());
+        assert_eq!(reloaded.run(), Ok(State::Halt));
+        assert_eq!(reloaded.read_observed(), Symbol::vec_from_str("11").unwrap());
+    }
+}
@@ -1,19 +1,64 @@
 pub mod bit_vec;
-use bit_vec::{USIZE_BIT_SIZE, get_bit, set_bit, unset_bit};
-use std::collections::{HashMap};
+mod binary;
+#[cfg(feature = "serde")]
+mod json;
+mod program;
+mod builder;
+mod dot;
+mod error;
+mod multi_tape;
+mod tape;
+#[cfg(feature = "wasm")]
+mod wasm;
+pub use builder::TuringMachineBuilder;
+pub use error::TuringError;
+pub use multi_tape::{MultiTapeRule, MultiTapeTuringMachine};
+pub use tape::{SparseTape, TapeStorage};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmTuringMachine;
+use bit_vec::{BitCell, USIZE_BIT_SIZE, bit_mask};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::io::Write;
 
 const DEFAULT_TAPE_SIZE: usize = 2; // this is not the actual tape size (number of bit-vectors)
+const DEFAULT_ALPHABET: [char; 2] = ['0', '1'];
+const BLANK_ALPHABET: [char; 3] = ['0', '1', '_'];
+const MAX_ALPHABET_SIZE: usize = u8::MAX as usize + 1;
+const TRACE_MAX_STEPS: u64 = 1_000_000; // step limit of traced runs, bounds the memory used by the trace
+const UINT_MAX_BITS: usize = u64::BITS as usize;
+const HEAD_MARKS_COLORED: (&str, &str) = ("\x1b[32m\x1b[4m", "\x1b[0m"); // green and underlined
+const HEAD_MARKS_PLAIN: (&str, &str) = ("[", "]");
+const HISTORY_MAX_LEN: usize = 10_000; // default number of configurations kept by `enable_history`
+const NONDETERMINISTIC_MAX_DEPTH: usize = 10_000; // maximum number of transitions explored along a single branch
 
 type ProgramStateId = u32;
 
-#[derive(Clone, Copy)]
+/// A configuration recorded by `enable_history`: the state, the position of the head within the window, the
+/// window itself, i.e. the symbols between the outermost non-blank ones and the head, and the leftmost and
+/// rightmost positions (see `position`) the head had been on.
+pub type HistoryEntry = (ProgramStateId, usize, Vec<Symbol>, (i64, i64));
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Left = -1,
     Right = 1,
     Stay = 0,
 }
 
-#[derive(Clone, Copy)]
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Left => write!(f, "L"),
+            Direction::Right => write!(f, "R"),
+            Direction::Stay => write!(f, "S"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransitionRule {
     pub from_state: ProgramState,
     pub from_symbol: Symbol,
@@ -26,39 +71,136 @@ impl TransitionRule {
     pub fn new(from_state: ProgramState, from_symbol: Symbol, new_symbol: Symbol, head_move_dir: Direction, to_state: State) -> TransitionRule {
         TransitionRule { from_state, from_symbol, new_symbol, head_move_dir, to_state }
     }
+
+    fn from_tuple((from_state, from_symbol, new_symbol, head_move_dir, to_state): RuleTuple) -> TransitionRule {
+        TransitionRule::new(ProgramState { id: from_state }, from_symbol, new_symbol, head_move_dir, State::from(to_state))
+    }
+}
+
+/// Target of a rule in the tuple form, see `RuleTuple`.
+#[derive(Clone, Copy, Debug)]
+pub enum StateTarget {
+    To(ProgramStateId),
+    Halt,
+    Term,
+}
+
+impl From<StateTarget> for State {
+    fn from(target: StateTarget) -> State {
+        match target {
+            StateTarget::To(id) => State::define(id),
+            StateTarget::Halt => State::Halt,
+            StateTarget::Term => State::Termination,
+        }
+    }
+}
+
+/// A transition rule written as `(from_state, from_symbol, new_symbol, direction, target)`,
+/// accepted by `define_transition_table` in place of `TransitionRule`s.
+pub type RuleTuple = (ProgramStateId, Symbol, Symbol, Direction, StateTarget);
+
+impl From<RuleTuple> for TransitionRule {
+    fn from(rule: RuleTuple) -> TransitionRule {
+        TransitionRule::from_tuple(rule)
+    }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Hash)]
-pub enum Symbol {
-    Zero = 0,
-    One = 1,
+/// A tape symbol, identified by its index in the machine's alphabet.
+/// `Symbol::Zero` and `Symbol::One` are the two symbols of the default binary alphabet.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol(u8);
+
+// symbols print as their index, which is `0`/`1` for the binary alphabet
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
+#[allow(non_upper_case_globals)]
 impl Symbol {
+    pub const Zero: Symbol = Symbol(0);
+    pub const One: Symbol = Symbol(1);
+
+    pub fn new(index: u8) -> Symbol {
+        Symbol(index)
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
     pub fn vec_from_numbers(numbers: &[u8]) -> Vec<Symbol> {
         numbers
             .iter()
-            .map(|num| {
-                match num {
-                    0 => Symbol::Zero,
-                    1 => Symbol::One,
-                    _ => panic!("Unexpected bit value: {}", num),
-                }
-            })
+            .map(|&num| Symbol(num))
             .collect::<Vec<Symbol>>()
     }
+
+    /// Parses a symbol written as its base-36 digit, i.e. `'0'`/`'1'` for the binary alphabet
+    /// and the digits of the `with_alphabet_size` alphabets in general.
+    pub fn from_char(c: char) -> Result<Symbol, TuringError> {
+        c.to_digit(36)
+            .map(|digit| Symbol(digit as u8))
+            .ok_or(TuringError::InvalidSymbol(c))
+    }
+
+    pub fn to_char(&self) -> Option<char> {
+        char::from_digit(self.0 as u32, 36)
+    }
+
+    pub fn vec_from_str(s: &str) -> Result<Vec<Symbol>, TuringError> {
+        s.chars().map(Symbol::from_char).collect()
+    }
+
+    /// The unary encoding of `n`, i.e. `n` ones.
+    pub fn unary(n: usize) -> Vec<Symbol> {
+        vec![Symbol::One; n]
+    }
+}
+
+/// A wildcard or default rule of a state, which applies to every symbol without a rule of its own in that state.
+/// A `new_symbol` of `None` writes back the symbol that was read, as in scanning loops.
+#[derive(Clone, Copy, Debug)]
+struct FallbackRule {
+    new_symbol: Option<Symbol>,
+    head_move_dir: Direction,
+    to_state: State,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProgramState {
     pub id: ProgramStateId,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum State {
     ProgramState(ProgramState),
-    Termination,
-    Halt,
+    Termination, // the regular end of a computation
+    Halt,        // an explicit halt, also entered when there is no rule for the symbol under the head, see `Outcome`
+    Accept,      // explicit accepting and rejecting halts of machines that recognize languages
+    Reject,
+}
+
+impl fmt::Display for ProgramState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "q{}", self.id)
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            State::ProgramState(state) => write!(f, "{}", state),
+            State::Termination => write!(f, "TERM"),
+            State::Halt => write!(f, "HALT"),
+            State::Accept => write!(f, "ACCEPT"),
+            State::Reject => write!(f, "REJECT"),
+        }
+    }
 }
 
 impl State {
@@ -67,104 +209,789 @@ impl State {
             ProgramState { id }
         )
     }
+
+    pub fn is_accepting(&self) -> bool {
+        matches!(self, State::Accept)
+    }
+
+    /// Whether the machine stops in this state, i.e. it is anything but a program state.
+    pub fn is_halting(&self) -> bool {
+        !matches!(self, State::ProgramState(_))
+    }
+}
+
+/// A transition the machine applied: the state it was in, the symbol it read and replaced, where the head moved
+/// and the head position before the move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TraceStep {
+    pub state_id: ProgramStateId,
+    pub read: Symbol,
+    pub written: Symbol,
+    pub direction: Direction,
+    pub head: usize,
+}
+
+/// Why a machine stopped, which tells the two ways of reaching `State::Halt` apart.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Accepted,     // a rule moved the machine to `State::Accept`
+    Rejected,     // a rule moved the machine to `State::Reject`
+    Halted,       // a rule moved the machine to `State::Halt`
+    HaltedNoRule, // there was no rule for the symbol under the head
+    Terminated,   // a rule moved the machine to `State::Termination`
+}
+
+/// Summary of a finished run.
+#[derive(Clone)]
+pub struct RunReport {
+    pub final_state: State,
+    pub outcome: Outcome,
+    pub steps: u64,
+    pub head_position: usize,
+    pub visited_states: Vec<ProgramStateId>, // the program state of every step, in execution order
 }
 
-pub struct TuringMachine {
-    tape: Vec<usize>, // bit-vector tape
+/// A single-tape machine, generic over the storage of its tape, by default a dense `Vec<BitCell>`.
+#[derive(Clone)]
+pub struct TuringMachine<T = Vec<BitCell>> {
+    tape: T, // bit-vector tape, every symbol takes `symbol_width` bits and is stored xor-ed with the blank
+    alphabet: Vec<char>,
+    blank: Symbol,
+    symbol_width: usize,
     head: usize,
+    origin: usize, // physical position of the cell at logical position 0, i.e. where the head started
     initial_state: Option<ProgramStateId>,
+    current_state: Option<State>,
+    steps: u64, // transitions applied since the machine was created or reset
+    halted_in: Option<ProgramStateId>, // the state that had no rule for the symbol under the head
+    visited_range: (i64, i64), // leftmost and rightmost positions the head has been on
+    history: Option<Vec<HistoryEntry>>, // recorded configurations, `None` unless enabled
+    history_limit: usize,
+    run_start: Option<(T, usize, usize, (i64, i64))>, // tape, head, origin and visited range before the first step, replayed by `step_back`
     states: HashMap<ProgramStateId, ProgramState>,
-    transition_table: HashMap<ProgramStateId, HashMap<Symbol, TransitionRule>>,
-    __visible_area: (usize, usize)
+    labels: HashMap<ProgramStateId, String>, // optional human readable names of states
+    accepting_states: HashSet<ProgramStateId>,
+    transition_table: HashMap<ProgramStateId, HashMap<Symbol, Vec<TransitionRule>>>,
+    wildcard_rules: HashMap<ProgramStateId, FallbackRule>,
+    default_rules: HashMap<ProgramStateId, FallbackRule>, // consulted after the wildcard rules
+    nondeterministic: bool,
+}
+
+/// Renders the observed area of the tape, i.e. the symbols between the outermost non-blank ones and the head,
+/// with the head in brackets, e.g. `110[1]0`.
+impl<T: TapeStorage> fmt::Display for TuringMachine<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (start, end) = self.observed_range();
+        write!(f, "{}", self.format_symbols(start, end, HEAD_MARKS_PLAIN))
+    }
+}
+
+// state, head position and the non-zero part of the tape (with the index of its first cell)
+type ConfigurationKey = (ProgramStateId, usize, usize, Vec<BitCell>);
+
+impl Default for TuringMachine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TuringMachine {
     pub fn new() -> TuringMachine {
-        TuringMachine {
-            tape: vec![0; DEFAULT_TAPE_SIZE as usize],
+        TuringMachine::with_alphabet(&DEFAULT_ALPHABET, DEFAULT_ALPHABET[0]).unwrap()
+    }
+
+    /// Creates a machine over an arbitrary finite alphabet. Symbols are indexed in the given order
+    /// and every untouched tape cell reads as `blank`.
+    pub fn with_alphabet(symbols: &[char], blank: char) -> Result<TuringMachine, TuringError> {
+        TuringMachine::with_storage(symbols, blank)
+    }
+
+    /// Creates a binary machine whose tape additionally has a blank symbol `_`, so that cells that were never written
+    /// can be told apart from written zeros. `Symbol::Zero` and `Symbol::One` keep their meaning.
+    pub fn with_blank() -> TuringMachine {
+        TuringMachine::with_alphabet(&BLANK_ALPHABET, BLANK_ALPHABET[2]).unwrap()
+    }
+
+    /// Creates a machine over the alphabet of the first `size` base-36 digits (`0`-`9`, then `a`-`z`),
+    /// where `0` is the blank. A size of 2 gives the same machine as `new`.
+    pub fn with_alphabet_size(size: usize) -> Result<TuringMachine, TuringError> {
+        if !(1..=36).contains(&size) {
+            return Err(TuringError::AlphabetSize { max: 36 });
+        }
+        let symbols = (0..size as u32).map(|digit| char::from_digit(digit, 36).unwrap()).collect::<Vec<_>>();
+        TuringMachine::with_alphabet(&symbols, symbols[0])
+    }
+}
+
+impl TuringMachine<SparseTape> {
+    /// Creates a binary machine like `new` whose tape only stores its non-zero cells.
+    pub fn sparse() -> TuringMachine<SparseTape> {
+        TuringMachine::sparse_with_alphabet(&DEFAULT_ALPHABET, DEFAULT_ALPHABET[0]).unwrap()
+    }
+
+    /// Creates a machine like `with_alphabet` whose tape only stores its non-zero cells.
+    pub fn sparse_with_alphabet(symbols: &[char], blank: char) -> Result<TuringMachine<SparseTape>, TuringError> {
+        TuringMachine::with_storage(symbols, blank)
+    }
+}
+
+impl<T: TapeStorage> TuringMachine<T> {
+    fn with_storage(symbols: &[char], blank: char) -> Result<TuringMachine<T>, TuringError> {
+        if symbols.is_empty() || symbols.len() > MAX_ALPHABET_SIZE {
+            return Err(TuringError::AlphabetSize { max: MAX_ALPHABET_SIZE });
+        }
+        if let Some(duplicate) = symbols.iter().enumerate().find_map(|(i, c)| symbols[..i].contains(c).then_some(c)) {
+            return Err(TuringError::DuplicateSymbol(*duplicate));
+        }
+        let blank = symbols
+            .iter()
+            .position(|&c| c == blank)
+            .ok_or(TuringError::BlankNotInAlphabet(blank))?;
+
+        // ceil(log2(alphabet size)) bits per symbol, but at least one
+        let symbol_width = ((usize::BITS - (symbols.len() - 1).leading_zeros()) as usize).max(1);
+
+        Ok(TuringMachine {
+            tape: T::with_cells(DEFAULT_TAPE_SIZE),
+            alphabet: symbols.to_vec(),
+            blank: Symbol(blank as u8),
+            symbol_width,
             initial_state: None,
-            head: DEFAULT_TAPE_SIZE / 2 * USIZE_BIT_SIZE, // set the head to the center of the tape by default
+            current_state: None,
+            steps: 0,
+            halted_in: None,
+            visited_range: (0, 0),
+            history: None,
+            history_limit: HISTORY_MAX_LEN,
+            run_start: None,
+            head: Self::initial_head(symbol_width), // set the head to the center of the tape by default
+            origin: Self::initial_head(symbol_width),
             states: HashMap::default(),
+            labels: HashMap::default(),
+            accepting_states: HashSet::default(),
             transition_table: HashMap::default(),
-            __visible_area: (0, 0),
+            wildcard_rules: HashMap::default(),
+            default_rules: HashMap::default(),
+            nondeterministic: false,
+        })
+    }
+
+    fn initial_head(symbol_width: usize) -> usize {
+        DEFAULT_TAPE_SIZE / 2 * USIZE_BIT_SIZE / symbol_width
+    }
+
+    /// Blanks the tape, recenters the head, clears the step counter and puts the machine back into its initial state,
+    /// keeping the alphabet, the states and the transition table.
+    pub fn reset(&mut self) {
+        self.tape = T::with_cells(DEFAULT_TAPE_SIZE);
+        self.head = Self::initial_head(self.symbol_width);
+        self.origin = self.head;
+        self.current_state = self.initial_state.map(State::define);
+        self.steps = 0;
+        self.halted_in = None;
+        self.visited_range = (0, 0);
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
         }
+        self.run_start = None;
     }
 
-    pub fn run(&mut self) -> Result<State, String> {
-        let initial_state = self.states.get(
-            &self.initial_state.ok_or("ERROR: initial state is not set")?,
-        ).unwrap();
+    /// Resets the machine and writes `cells` at the head, ready to run on a new input. Fails like `write_at`.
+    pub fn reset_with_input(&mut self, cells: &[Symbol]) -> Result<(), TuringError> {
+        self.reset();
+        self.write_to_tape(cells)
+    }
 
-        let mut current_state = State::ProgramState(*initial_state);
-        
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    pub fn blank(&self) -> Symbol {
+        self.blank
+    }
+
+    pub fn symbol(&self, c: char) -> Result<Symbol, TuringError> {
+        self.alphabet
+            .iter()
+            .position(|&symbol| symbol == c)
+            .map(|index| Symbol(index as u8))
+            .ok_or(TuringError::UnknownSymbol(c))
+    }
+
+    pub fn symbol_char(&self, symbol: Symbol) -> Option<char> {
+        self.alphabet.get(symbol.0 as usize).copied()
+    }
+
+    /// Runs the machine until it halts or terminates, without producing any output.
+    pub fn run(&mut self) -> Result<State, TuringError> {
+        self.run_reporting().map(|report| report.final_state)
+    }
+
+    /// Runs the machine like `run`, writing the state and the tape of every step to `output`.
+    pub fn run_with_output(&mut self, output: &mut dyn Write) -> Result<State, TuringError> {
+        self.run_until(u64::MAX, Some(output), None).map(|report| report.final_state)
+    }
+
+    /// Runs the machine like `run`, recording every applied transition as a `TraceStep`.
+    /// Fails once `TRACE_MAX_STEPS` steps have been applied so that the trace stays bounded.
+    pub fn run_traced(&mut self) -> Result<(State, Vec<TraceStep>), TuringError> {
+        let mut trace = Vec::new();
+        let report = self.run_until(TRACE_MAX_STEPS, None, Some(&mut trace))?;
+        Ok((report.final_state, trace))
+    }
+
+    /// Runs the machine like `run`, but fails if it has not halted or terminated after `max_steps` applied transitions.
+    pub fn run_with_limit(&mut self, max_steps: u64) -> Result<State, TuringError> {
+        self.run_until(max_steps, None, None).map(|report| report.final_state)
+    }
+
+    pub fn run_reporting(&mut self) -> Result<RunReport, TuringError> {
+        self.run_until(u64::MAX, None, None)
+    }
+
+    /// Runs the machine like `run`, calling `observer` with the machine and the rule about to be applied before every
+    /// transition, so the machine it sees is still in the configuration the rule applies to. Returning `false` from
+    /// the observer stops the run before that rule is applied and returns the current state, from which the machine
+    /// can be resumed later.
+    pub fn run_with_observer<F>(&mut self, mut observer: F) -> Result<State, TuringError>
+    where
+        F: FnMut(&TuringMachine<T>, &TransitionRule) -> bool,
+    {
         loop {
-            match current_state {
-                State::ProgramState(ProgramState { id: state_id }) => {
-                    print!("q{}: ", state_id);
-                    self.print_tape();
-                    let current_symbol = self.get_head_value();
-                    let transition_rule = self.get_transition_rule(&state_id, &current_symbol);
-                    match transition_rule {
-                        Some(TransitionRule { to_state, new_symbol, head_move_dir, .. }) => {
-                            let new_symbol = *new_symbol;
-                            let head_move_dir = *head_move_dir;
-                            current_state = *to_state;
-                            self.set_head_value(new_symbol);
-                            self.move_head(head_move_dir);
-                        }
-                        None => return Ok(State::Halt),
-                    }
-                },
-                state => return Ok(state)
+            let state_id = match self.current_state.ok_or(TuringError::InitialStateNotSet)? {
+                State::ProgramState(ProgramState { id }) => id,
+                state => return Ok(state),
+            };
+            if let Some(rule) = self.get_transition_rule(&state_id, &self.get_head_value()) {
+                if !observer(self, &rule) {
+                    return Ok(State::define(state_id));
+                }
+            }
+            self.step()?;
+        }
+    }
+
+    /// Runs a copy of the machine, reset and given `input` (see `reset_with_input`), leaving the receiver untouched.
+    pub fn execute(&self, input: &[Symbol]) -> Result<RunReport, TuringError> {
+        let mut machine = self.clone();
+        machine.reset_with_input(input)?;
+        machine.run_reporting()
+    }
+
+    /// Whether both machines, each reset and run for at most `max_steps` steps, behave the same on every input,
+    /// see `first_divergence`.
+    pub fn behaves_like<U: TapeStorage>(&self, other: &TuringMachine<U>, inputs: &[Vec<Symbol>], max_steps: u64) -> bool {
+        self.first_divergence(other, inputs, max_steps).is_none()
+    }
+
+    /// Runs copies of both machines on every input and returns the index of the first input on which they stop
+    /// in a different way (see `Outcome`), fail differently, or leave different symbols in the observed area.
+    /// Neither machine is mutated.
+    pub fn first_divergence<U: TapeStorage>(&self, other: &TuringMachine<U>, inputs: &[Vec<Symbol>], max_steps: u64) -> Option<usize> {
+        inputs.iter().position(|input| {
+            let (mut this, mut that) = (self.clone(), other.clone());
+            let written = (this.reset_with_input(input), that.reset_with_input(input));
+            if written.0.is_err() || written.1.is_err() {
+                return written.0 != written.1;
+            }
+            let results = (this.run_with_limit(max_steps), that.run_with_limit(max_steps));
+            results.0 != results.1 || this.outcome() != that.outcome() || this.read_observed() != that.read_observed()
+        })
+    }
+
+    fn run_until(
+        &mut self,
+        max_steps: u64,
+        mut output: Option<&mut dyn Write>,
+        mut trace: Option<&mut Vec<TraceStep>>,
+    ) -> Result<RunReport, TuringError> {
+        let mut steps = 0;
+        let mut visited_states = Vec::new();
+        loop {
+            let state_id = match self.current_state.ok_or(TuringError::InitialStateNotSet)? {
+                State::ProgramState(ProgramState { id }) => id,
+                final_state => {
+                    let outcome = self.outcome().unwrap();
+                    return Ok(RunReport { final_state, outcome, steps, head_position: self.head, visited_states });
+                }
+            };
+            if let Some(output) = output.as_mut() {
+                writeln!(output, "{}: {}", self.format_state(state_id), self.format_tape(true))
+                    .map_err(|err| TuringError::Io(format!("failed to write the trace: {}", err)))?;
+            }
+            visited_states.push(state_id);
+
+            // reaching a symbol without a rule halts the machine without applying a transition
+            let read = self.get_head_value();
+            if let Some(rule) = self.get_transition_rule(&state_id, &read) {
+                if steps == max_steps {
+                    return Err(TuringError::StepLimitExceeded(steps));
+                }
+                steps += 1;
+                if let Some(trace) = trace.as_mut() {
+                    trace.push(TraceStep { state_id, read, written: rule.new_symbol, direction: rule.head_move_dir, head: self.head });
+                }
+            }
+            self.step()?;
+        }
+    }
+
+    /// Runs the machine for at most `max_steps` steps and returns the step at which it re-entered a configuration
+    /// (state, head position and tape contents) it has already been in, which proves that it never halts.
+    /// Returns `None` if the machine stops or no repetition is found within the limit.
+    /// Only machines that keep using a bounded part of the tape can be caught this way.
+    pub fn detect_loop(&mut self, max_steps: usize) -> Option<usize> {
+        let mut seen_configurations = HashSet::new();
+        for step in 0..=max_steps {
+            let state_id = match self.current_state? {
+                State::ProgramState(ProgramState { id }) => id,
+                _ => return None,
+            };
+            if !seen_configurations.insert(self.configuration_key(state_id)) {
+                return Some(step);
+            }
+            if step < max_steps {
+                self.step().ok()?;
+            }
+        }
+        None
+    }
+
+    /// Runs the machine like `run_with_limit`, but fails as soon as it re-enters a configuration it has already
+    /// been in, as such a machine provably never halts.
+    /// Every visited configuration is remembered, keyed by the state, the head position and the cells between
+    /// the first and the last non-zero cell, so memory and time per step grow with the written part of the tape
+    /// rather than with the whole allocated tape.
+    pub fn run_detect_loop(&mut self, max_steps: u64) -> Result<State, TuringError> {
+        let mut seen_configurations = HashSet::new();
+        let mut steps = 0;
+        loop {
+            let state_id = match self.current_state.ok_or(TuringError::InitialStateNotSet)? {
+                State::ProgramState(ProgramState { id }) => id,
+                state => return Ok(state),
+            };
+            if !seen_configurations.insert(self.configuration_key(state_id)) {
+                return Err(TuringError::LoopDetected(steps));
+            }
+            if self.get_transition_rule(&state_id, &self.get_head_value()).is_some() {
+                if steps == max_steps {
+                    return Err(TuringError::StepLimitExceeded(steps));
+                }
+                steps += 1;
+            }
+            self.step()?;
+        }
+    }
+
+    fn configuration_key(&self, state_id: ProgramStateId) -> ConfigurationKey {
+        let (first_cell, last_cell) = self.tape.written_cells().unwrap_or((0, 0));
+        (state_id, self.head, first_cell, (first_cell..=last_cell).map(|index| self.tape.cell(index)).collect())
+    }
+
+    /// Applies a single transition from the current state and returns the state the machine moved to.
+    /// Once the machine has halted or terminated, further steps leave it untouched.
+    pub fn step(&mut self) -> Result<State, TuringError> {
+        let state_id = match self.current_state.ok_or(TuringError::InitialStateNotSet)? {
+            State::ProgramState(ProgramState { id }) => id,
+            state => return Ok(state),
+        };
+
+        if self.run_start.is_none() {
+            self.run_start = Some((self.tape.clone(), self.head, self.origin, self.visited_range));
+        }
+        self.record_history(state_id);
+        let current_symbol = self.get_head_value();
+        let next_state = match self.get_transition_rule(&state_id, &current_symbol) {
+            Some(TransitionRule { to_state, new_symbol, head_move_dir, .. }) => {
+                self.set_symbol(self.head, new_symbol);
+                self.move_head(head_move_dir);
+                self.steps += 1;
+                to_state
+            }
+            None => {
+                self.halted_in = Some(state_id);
+                State::Halt
+            }
+        };
+
+        self.current_state = Some(next_state);
+        Ok(next_state)
+    }
+
+    /// Reverts the last `step`. The previous configuration is restored from `history` if it was recorded, and
+    /// otherwise by replaying the run from the tape the first step started from. Changes made to the tape in between
+    /// steps are not replayed. Fails if no step has been made since the machine was created or reset, and with
+    /// `TuringError::ReplayDiverged`, leaving the machine untouched, if such changes make the replay halt early.
+    pub fn step_back(&mut self) -> Result<(), TuringError> {
+        if self.steps == 0 && self.halted_in.is_none() {
+            return Err(TuringError::NoStepToUndo);
+        }
+
+        // the history is complete up to the last step as long as it has not reached its limit
+        let recorded = self.history.as_ref().is_some_and(|history| !history.is_empty() && history.len() < self.history_limit);
+        if recorded {
+            let (state_id, head_offset, window, visited_range) = self.history.as_mut().unwrap().pop().unwrap();
+            if self.halted_in.take().is_none() {
+                let rule = self.get_transition_rule(&state_id, &window[head_offset]).unwrap();
+                let previous_head = match rule.head_move_dir {
+                    Direction::Left => self.head + 1,
+                    Direction::Right => self.head - 1,
+                    Direction::Stay => self.head,
+                };
+                if let Some((first, last)) = self.written_range() {
+                    (first..=last).for_each(|position| self.set_symbol(position, self.blank));
+                }
+                let start = previous_head - head_offset;
+                window.iter().enumerate().for_each(|(i, symbol)| self.set_symbol(start + i, *symbol));
+                self.head = previous_head;
+                self.visited_range = visited_range;
+                self.steps -= 1;
+            }
+            self.current_state = Some(State::define(state_id));
+            return Ok(());
+        }
+
+        // a halt without a rule did not count as a step, so undoing it replays every step
+        let target = if self.halted_in.is_some() { self.steps } else { self.steps - 1 };
+        let (tape, head, origin, visited_range) = self.run_start.clone().unwrap();
+        let mut history = self.history.take();
+        let stopped = (self.tape.clone(), self.head, self.origin, self.visited_range, self.current_state, self.steps, self.halted_in);
+        (self.tape, self.head, self.origin, self.visited_range) = (tape, head, origin, visited_range);
+        self.current_state = self.initial_state.map(State::define);
+        self.steps = 0;
+        self.halted_in = None;
+        while self.steps < target {
+            if self.step()?.is_halting() {
+                let replayed = self.steps;
+                (self.tape, self.head, self.origin, self.visited_range, self.current_state, self.steps, self.halted_in) = stopped;
+                self.history = history;
+                return Err(TuringError::ReplayDiverged(replayed));
             }
         }
+        // a full history may still hold the configuration of the undone step
+        if let Some(history) = history.as_mut() {
+            history.truncate(self.steps as usize);
+        }
+        self.history = history;
+        Ok(())
+    }
+
+    /// How the machine stopped, `None` while it is still running or its initial state is not set.
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self.current_state? {
+            State::ProgramState(_) => None,
+            State::Accept => Some(Outcome::Accepted),
+            State::Reject => Some(Outcome::Rejected),
+            State::Halt if self.halted_in.is_some() => Some(Outcome::HaltedNoRule),
+            State::Halt => Some(Outcome::Halted),
+            State::Termination => Some(Outcome::Terminated),
+        }
+    }
+
+    /// Marks program states as accepting, see `accepts`.
+    pub fn set_accepting_states(&mut self, state_ids: &[ProgramStateId]) -> Result<(), TuringError> {
+        if let Some(&id) = state_ids.iter().find(|id| !self.states.contains_key(id)) {
+            return Err(TuringError::StateNotDefined(id));
+        }
+        self.accepting_states = state_ids.iter().copied().collect();
+        Ok(())
+    }
+
+    /// Resets the machine, runs it on `input` and returns whether it accepted, which is the case if it moved to
+    /// `State::Accept` or stopped in an accepting program state because there was no rule for the symbol under the
+    /// head. Stopping in `State::Halt`, `State::Termination` or `State::Reject` rejects.
+    pub fn accepts(&mut self, input: &[Symbol]) -> Result<bool, TuringError> {
+        self.reset_with_input(input)?;
+        Ok(match self.run()? {
+            State::Accept => true,
+            State::Halt => self.halted_in.is_some_and(|id| self.accepting_states.contains(&id)),
+            _ => false,
+        })
+    }
+
+    /// Number of transitions applied since the machine was created or last reset, across all runs and steps.
+    /// Halting on a symbol without a rule applies no transition and is not counted.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Starts or stops recording the configuration every `step` starts from, see `history`. Disabling it drops the
+    /// recorded configurations.
+    pub fn enable_history(&mut self, enabled: bool) {
+        self.history = enabled.then(|| self.history.take().unwrap_or_default());
+    }
+
+    /// Sets the maximum number of configurations `history` keeps, `HISTORY_MAX_LEN` by default. Configurations
+    /// past the limit are not recorded.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        if let Some(history) = self.history.as_mut() {
+            history.truncate(limit);
+        }
+    }
+
+    /// The configurations the machine passed through since history was enabled or the machine was reset, oldest first.
+    pub fn history(&self) -> &[HistoryEntry] {
+        self.history.as_deref().unwrap_or_default()
+    }
+
+    fn record_history(&mut self, state_id: ProgramStateId) {
+        if self.history.as_ref().is_none_or(|history| history.len() >= self.history_limit) {
+            return;
+        }
+        let (start, end) = self.observed_range();
+        let entry = (state_id, self.head - start, self.read_tape_range(start, end + 1), self.visited_range);
+        self.history.as_mut().unwrap().push(entry);
+    }
+
+    /// The state the next `step` starts from, `None` until the initial state is set.
+    pub fn current_state(&self) -> Option<State> {
+        self.current_state
+    }
+
+    /// Returns the rule bound to the given state and symbol, falling back to the wildcard rule and then
+    /// to the default rule of the state. In nondeterministic mode this is the first of the rules bound to them.
+    pub fn get_transition_rule(&self, state_id: &ProgramStateId, symbol: &Symbol) -> Option<TransitionRule> {
+        self.get_transition_rules(state_id, symbol)
+            .first()
+            .copied()
+            .or_else(|| Self::apply_fallback_rule(&self.wildcard_rules, state_id, symbol))
+            .or_else(|| Self::apply_fallback_rule(&self.default_rules, state_id, symbol))
+    }
+
+    // the fallback rule of the state, as applied to `symbol`
+    fn apply_fallback_rule(
+        rules: &HashMap<ProgramStateId, FallbackRule>,
+        state_id: &ProgramStateId,
+        symbol: &Symbol,
+    ) -> Option<TransitionRule> {
+        rules.get(state_id).map(|rule| TransitionRule::new(
+            ProgramState { id: *state_id },
+            *symbol,
+            rule.new_symbol.unwrap_or(*symbol),
+            rule.head_move_dir,
+            rule.to_state,
+        ))
+    }
+
+    // the explicit rules bound to the state and symbol, or the rule the lookup falls back to if there are none
+    fn matching_rules(&self, state_id: &ProgramStateId, symbol: &Symbol) -> Vec<TransitionRule> {
+        match self.get_transition_rules(state_id, symbol) {
+            [] => self.get_transition_rule(state_id, symbol).into_iter().collect(),
+            rules => rules.to_vec(),
+        }
     }
 
-    pub fn get_transition_rule(&self, state_id: &ProgramStateId, symbol: &Symbol) -> Option<&TransitionRule> {
+    pub fn get_transition_rules(&self, state_id: &ProgramStateId, symbol: &Symbol) -> &[TransitionRule] {
         self.transition_table
-            .get(&state_id).unwrap()
-            .get(symbol)
+            .get(state_id)
+            .and_then(|rules| rules.get(symbol))
+            .map_or(&[], |rules| rules.as_slice())
     }
 
-    pub fn set_initial_state(&mut self, state_id: ProgramStateId) -> Result<(), String> {
+    /// Allows binding several transition rules to the same `(from_state, from_symbol)` pair.
+    /// Should be set before defining the transition table, as the duplicate check happens there.
+    pub fn set_nondeterministic(&mut self, nondeterministic: bool) {
+        self.nondeterministic = nondeterministic;
+    }
+
+    /// Explores all branches of a nondeterministic machine breadth-first, starting from its current configuration.
+    /// Returns `true` as soon as any branch terminates or accepts and `false` if every branch halted, rejected or
+    /// exceeded `NONDETERMINISTIC_MAX_DEPTH` transitions.
+    pub fn run_nondeterministic(&self) -> Result<bool, TuringError> {
+        self.run_search(NONDETERMINISTIC_MAX_DEPTH)
+            .map(|state| matches!(state, State::Termination | State::Accept))
+    }
+
+    /// Explores all branches like `run_nondeterministic`, following every branch for at most `max_depth` transitions.
+    /// Returns the state of the first branch that terminates or accepts, or `State::Reject` if none does.
+    /// The tape is cloned for every branch, the receiver is left untouched.
+    pub fn run_search(&self, max_depth: usize) -> Result<State, TuringError> {
+        let state_id = match self.current_state.ok_or(TuringError::InitialStateNotSet)? {
+            State::ProgramState(ProgramState { id }) => id,
+            state @ (State::Termination | State::Accept) => return Ok(state),
+            _ => return Ok(State::Reject),
+        };
+
+        // a scratch copy of the machine, the tape and the head of every explored branch are swapped into it
+        let mut machine = self.clone();
+        let mut branches = VecDeque::from([(self.tape.clone(), self.head, state_id, 0)]);
+
+        while let Some((tape, head, state_id, depth)) = branches.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            machine.tape = tape;
+            machine.head = head;
+            let current_symbol = machine.get_head_value();
+            let rules = machine.matching_rules(&state_id, &current_symbol);
+
+            let (tape, head) = (std::mem::take(&mut machine.tape), machine.head);
+            for rule in rules {
+                machine.tape = tape.clone();
+                machine.head = head;
+                machine.set_symbol(machine.head, rule.new_symbol);
+                machine.move_head(rule.head_move_dir);
+
+                match rule.to_state {
+                    State::ProgramState(ProgramState { id }) => {
+                        branches.push_back((std::mem::take(&mut machine.tape), machine.head, id, depth + 1));
+                    },
+                    state @ (State::Termination | State::Accept) => return Ok(state),
+                    State::Halt | State::Reject => {},
+                }
+            }
+        }
+        Ok(State::Reject)
+    }
+
+    pub fn set_initial_state(&mut self, state_id: ProgramStateId) -> Result<(), TuringError> {
         if !self.states.contains_key(&state_id) {
-            return Err(format!("ERROR: state with id `{}` is not defined", &state_id));
+            return Err(TuringError::StateNotDefined(state_id));
         }
         self.initial_state = Some(state_id);
+        self.current_state = Some(State::define(state_id));
+        self.halted_in = None;
         Ok(())
     }
 
-    pub fn define_states(&mut self, program_states: &Vec<ProgramState>, ) {
+    pub fn define_states(&mut self, program_states: &[ProgramState]) {
         program_states.iter().for_each(|state| {
             self.states.insert(state.id, *state);
         });
     }
 
-    pub fn define_transition_table(&mut self, transition_rules: &Vec<TransitionRule>) -> Result<(), String> {
-        self.validate_transition_rules(transition_rules)?;
+    /// Defines states like `define_states`, attaching a label to each one. Labels are only used for display
+    /// and lookup with `state_by_name`, states are still referred to by id everywhere else.
+    pub fn define_named_states(&mut self, named_states: &[(ProgramStateId, &str)]) {
+        named_states.iter().for_each(|(id, label)| {
+            self.states.insert(*id, ProgramState { id: *id });
+            self.labels.insert(*id, label.to_string());
+        });
+    }
+
+    pub fn state_label(&self, state_id: ProgramStateId) -> Option<&str> {
+        self.labels.get(&state_id).map(String::as_str)
+    }
+
+    pub fn state_by_name(&self, name: &str) -> Option<ProgramStateId> {
+        self.labels
+            .iter()
+            .find_map(|(id, label)| (label == name).then_some(*id))
+    }
+
+    /// The name of a program state as used in traces, `q_<label>` for labeled states and `q<id>` otherwise.
+    pub fn format_state(&self, state_id: ProgramStateId) -> String {
+        match self.state_label(state_id) {
+            Some(label) => format!("q_{}", label),
+            None => format!("q{}", state_id),
+        }
+    }
+
+    /// Binds the given rules, either `TransitionRule`s or `RuleTuple`s, to their states and symbols.
+    pub fn define_transition_table<R: Copy + Into<TransitionRule>>(&mut self, transition_rules: &[R]) -> Result<(), TuringError> {
+        let transition_rules = transition_rules.iter().map(|&rule| rule.into()).collect::<Vec<TransitionRule>>();
+        self.validate_transition_rules(&transition_rules)?;
         
-        for t in transition_rules {
+        for t in &transition_rules {
             let from_state = &t.from_state;
             let from_symbol = &t.from_symbol;
-            if !self.transition_table.contains_key(&from_state.id) {
-                self.transition_table.insert(from_state.id, HashMap::default());
+            let rules = self.transition_table
+                .entry(from_state.id)
+                .or_default()
+                .entry(*from_symbol)
+                .or_default();
+            if !self.nondeterministic {
+                rules.clear();
+            }
+            rules.push(*t);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every state reachable from the initial state has a rule for every symbol of the alphabet,
+    /// to tell forgotten rules apart from intended halts. Wildcard and default rules count as rules for every symbol.
+    /// Reports every missing `(state, symbol)` pair as a `TuringError::MissingRule`, in the order the states are
+    /// reached.
+    pub fn validate_completeness(&self) -> Result<(), Vec<TuringError>> {
+        let initial_state = self.initial_state.ok_or(vec![TuringError::InitialStateNotSet])?;
+
+        let mut missing_rules = Vec::new();
+        let mut reached = HashSet::from([initial_state]);
+        let mut pending = VecDeque::from([initial_state]);
+        while let Some(state) = pending.pop_front() {
+            for symbol in (0..self.alphabet.len()).map(|index| Symbol(index as u8)) {
+                let rules = self.matching_rules(&state, &symbol);
+                if rules.is_empty() {
+                    missing_rules.push(TuringError::MissingRule { state, symbol });
+                }
+                for rule in rules {
+                    if let State::ProgramState(ProgramState { id }) = rule.to_state {
+                        if reached.insert(id) {
+                            pending.push_back(id);
+                        }
+                    }
+                }
             }
-            self.transition_table
-                .get_mut(&from_state.id).unwrap()
-                .insert(*from_symbol, *t);
         }
 
+        match missing_rules.is_empty() {
+            true => Ok(()),
+            false => Err(missing_rules),
+        }
+    }
+
+    /// Binds a rule to `from_state` that applies to every symbol without an explicit rule of its own in that state,
+    /// replacing the previous wildcard rule of the state. With a `new_symbol` of `None` the symbol that was read
+    /// is written back.
+    pub fn define_wildcard_rule(
+        &mut self,
+        from_state: ProgramState,
+        new_symbol: Option<Symbol>,
+        head_move_dir: Direction,
+        to_state: State,
+    ) -> Result<(), TuringError> {
+        self.validate_fallback_rule(from_state, new_symbol)?;
+        self.wildcard_rules.insert(from_state.id, FallbackRule { new_symbol, head_move_dir, to_state });
+        Ok(())
+    }
+
+    /// Binds a catch-all rule to `state`, used as a last resort when the symbol read has neither an explicit rule
+    /// nor a wildcard rule in that state. Replaces the previous default rule of the state.
+    pub fn define_default_rule(
+        &mut self,
+        state: ProgramState,
+        new_symbol: Symbol,
+        head_move_dir: Direction,
+        to_state: State,
+    ) -> Result<(), TuringError> {
+        self.validate_fallback_rule(state, Some(new_symbol))?;
+        self.default_rules.insert(state.id, FallbackRule { new_symbol: Some(new_symbol), head_move_dir, to_state });
+        Ok(())
+    }
+
+    fn validate_fallback_rule(&self, from_state: ProgramState, new_symbol: Option<Symbol>) -> Result<(), TuringError> {
+        if !self.states.contains_key(&from_state.id) {
+            return Err(TuringError::StateNotDefined(from_state.id));
+        }
+        if let Some(symbol) = new_symbol.filter(|symbol| self.symbol_char(*symbol).is_none()) {
+            return Err(TuringError::SymbolNotInAlphabet(symbol));
+        }
         Ok(())
     }
 
-    fn validate_transition_rules(&self, transition_rules: &Vec<TransitionRule>) -> Result<(), String> {
+    fn validate_transition_rules(&self, transition_rules: &[TransitionRule]) -> Result<(), TuringError> {
         let mut states_used = HashMap::<&ProgramStateId, Vec<Symbol>>::new();
 
         for t in transition_rules {
             let from_state = &t.from_state;
             let from_symbol = &t.from_symbol;
             if !self.states.contains_key(&from_state.id) {
-                return Err(format!("ERROR: State with id `{}` does not exist", from_state.id));
+                return Err(TuringError::StateNotDefined(from_state.id));
+            }
+            if let Some(symbol) = [t.from_symbol, t.new_symbol].into_iter().find(|s| self.symbol_char(*s).is_none()) {
+                return Err(TuringError::SymbolNotInAlphabet(symbol));
             }
             
             if !states_used.contains_key(&from_state.id) {
@@ -172,120 +999,2113 @@ impl TuringMachine {
             }
             
             let already_mapped_symbols = states_used.get_mut(&from_state.id).unwrap();
-            if already_mapped_symbols.contains(from_symbol) {
-                return Err(format!("ERROR: State with id `{}` is already bound to a transition rule as a `from_state`", from_state.id));
+            if !self.nondeterministic && already_mapped_symbols.contains(from_symbol) {
+                return Err(TuringError::DuplicateRule {
+                    state: from_state.id,
+                    label: self.state_label(from_state.id).map(str::to_string),
+                    symbol: *from_symbol,
+                });
             }
             already_mapped_symbols.push(*from_symbol);
         }
         Ok(())
     }
 
-    pub fn write_to_tape(&mut self, cells: &[Symbol]) {
-        // TODO: add check for tape size and dynamically reallocate tape if needed
-        assert!(cells.len() > self.tape.len(), "The length of the cells to be written to the tape should be less than the tape size");
-        
-        cells
-            .chunks(USIZE_BIT_SIZE)
-            .enumerate()
-            .for_each(|(i, chunk)| {
-                let current_head = self.head + i * USIZE_BIT_SIZE;
-                let cell = &mut self.tape[current_head / USIZE_BIT_SIZE];
-
-                chunk.iter().enumerate().for_each(|(j, symbol)| {
-                    let bit_idx = self.head % USIZE_BIT_SIZE + j;
-                    match symbol {
-                        Symbol::Zero => unset_bit(cell, &bit_idx),
-                        Symbol::One => set_bit(cell, &bit_idx),
-                    }
-                });
-            }
-        );
+    /// Writes `cells` starting at the head, see `write_at`.
+    pub fn write_to_tape(&mut self, cells: &[Symbol]) -> Result<(), TuringError> {
+        self.write_at(self.head, cells)
     }
 
-    pub fn head(&self) -> usize { self.head }
+    /// Writes `n` in unary, i.e. `n` consecutive `Symbol::One`s, starting at the head. `count_ones_run` reads it back.
+    pub fn write_unary(&mut self, n: usize) -> Result<(), TuringError> {
+        self.write_to_tape(&Symbol::unary(n))
+    }
 
-    pub fn print_tape_observed_area(&self, offset: Option<usize>) {
-        let offset = offset.unwrap_or(0);
-        let start = {
-            let first_non_zero_idx = self.tape.iter().position(|&x| x != 0).unwrap_or(0) as isize - offset as isize;
-            first_non_zero_idx.max(0) as usize
-        };
-        let last_non_zero_idx = {
-            let last_non_zero_idx = self.tape.iter().rposition(|&x| x != 0).unwrap_or(self.tape.len() - 1) + offset;
-            last_non_zero_idx.min(self.tape.len() - 1)
-        };
-        let observed_area = &self.tape[start..last_non_zero_idx];
+    /// Writes `a` and `b` in unary separated by a `Symbol::Zero` starting at the head, the input layout of the
+    /// adder in `main.rs`.
+    pub fn write_unary_pair(&mut self, a: usize, b: usize) -> Result<(), TuringError> {
+        let mut cells = Symbol::unary(a);
+        cells.push(Symbol::Zero);
+        cells.extend(Symbol::unary(b));
+        self.write_to_tape(&cells)
+    }
 
-        observed_area.iter().for_each(|cell| {
-            print!("{:032b}", cell);
-        });
-        println!();
+    /// Writes `cells` starting at position `start`, independently of the head.
+    /// Like every write, the tape grows to the right if the symbols do not fit into it.
+    /// Fails without writing anything if one of the symbols is not part of the alphabet.
+    pub fn write_at(&mut self, start: usize, cells: &[Symbol]) -> Result<(), TuringError> {
+        if let Some(symbol) = cells.iter().find(|symbol| self.symbol_char(**symbol).is_none()) {
+            return Err(TuringError::SymbolNotInAlphabet(*symbol));
+        }
+        self.ensure_tape_len(start + cells.len());
+
+        cells
+            .iter()
+            .enumerate()
+            .for_each(|(i, symbol)| self.set_symbol(start + i, *symbol));
+        Ok(())
+    }
+
+    /// Puts the head on the physical `position`, e.g. on the first cell of an input written with `write_at`.
+    /// The tape grows to the right if the position is beyond it. Placing the head is not a move, so the range the
+    /// head has visited (see `visited_range`) starts over at the new position.
+    pub fn set_head(&mut self, position: usize) {
+        self.ensure_tape_len(position + 1);
+        self.head = position;
+        let position = self.position();
+        self.visited_range = (position, position);
+    }
+
+    /// Physical index of the head in the tape storage, which shifts whenever the tape grows to the left.
+    /// Use `position` for a coordinate that stays stable.
+    pub fn head(&self) -> usize { self.head }
+
+    /// Logical position of the head relative to the cell it started on, negative positions are left of it.
+    /// The tape is unbounded in both directions, so the head can move arbitrarily far either way.
+    pub fn position(&self) -> i64 {
+        self.head as i64 - self.origin as i64
+    }
+
+    /// Reads `len` symbols starting at position `start`, positions beyond the tape read as blank.
+    pub fn read_tape(&self, start: usize, len: usize) -> Vec<Symbol> {
+        (start..start + len).map(|position| self.symbol_at(position)).collect()
+    }
+
+    /// Reads the symbols in the half-open range `[start, end)`, clamped to the allocated tape.
+    pub fn read_tape_range(&self, start: usize, end: usize) -> Vec<Symbol> {
+        let end = end.min(self.tape_len());
+        (start.min(end)..end).map(|position| self.get_symbol(position)).collect()
+    }
+
+    /// The symbol at the given position, positions beyond the tape read as blank.
+    pub fn symbol_at(&self, position: usize) -> Symbol {
+        if position < self.tape_len() { self.get_symbol(position) } else { self.blank }
+    }
+
+    /// Reads the cell at `position` of a tape split into `tracks` interleaved tracks, i.e. the symbols
+    /// of the physical cells `position * tracks .. (position + 1) * tracks`, one per track.
+    pub fn read_tracks(&self, tracks: usize, position: usize) -> Vec<Symbol> {
+        self.read_tape(position * tracks, tracks)
+    }
+
+    /// Writes one symbol per track to the cell at `position` of a tape split into `symbols.len()` interleaved tracks,
+    /// see `read_tracks`.
+    pub fn write_tracks(&mut self, position: usize, symbols: &[Symbol]) -> Result<(), TuringError> {
+        self.write_at(position * symbols.len(), symbols)
+    }
+
+    /// Iterates over the symbols of the whole allocated tape, from its leftmost cell.
+    pub fn tape_symbols(&self) -> impl Iterator<Item = Symbol> + '_ {
+        (0..self.tape_len()).map(|position| self.get_symbol(position))
+    }
+
+    /// Reads the symbols between the first and the last non-blank cell of the tape, both included.
+    pub fn read_observed(&self) -> Vec<Symbol> {
+        self.written_range()
+            .map_or(Vec::new(), |(first, last)| self.read_tape(first, last - first + 1))
+    }
+
+    /// Interprets `len` cells starting at position `start` as an unsigned binary number, most significant bit first.
+    /// Fails if `len` exceeds 64 or any of the cells holds something other than `Symbol::Zero`/`Symbol::One`.
+    pub fn read_uint(&self, start: usize, len: usize) -> Result<u64, TuringError> {
+        if len > UINT_MAX_BITS {
+            return Err(TuringError::UintTooWide(len));
+        }
+        self.read_tape(start, len)
+            .into_iter()
+            .try_fold(0, |value, symbol| match symbol {
+                Symbol::Zero | Symbol::One => Ok((value << 1) | symbol.0 as u64),
+                _ => Err(TuringError::NotBinary(symbol)),
+            })
+    }
+
+    /// Writes `value` as a `len` bit unsigned binary number starting at position `start`, most significant bit first.
+    /// Fails if `len` exceeds 64 or `value` does not fit into `len` bits.
+    pub fn write_uint(&mut self, value: u64, start: usize, len: usize) -> Result<(), TuringError> {
+        if len > UINT_MAX_BITS {
+            return Err(TuringError::UintTooWide(len));
+        }
+        if len < UINT_MAX_BITS && value >> len != 0 {
+            return Err(TuringError::ValueTooLarge { value, len });
+        }
+        if value != 0 && self.symbol_char(Symbol::One).is_none() {
+            return Err(TuringError::SymbolNotInAlphabet(Symbol::One));
+        }
+
+        self.ensure_tape_len(start + len);
+        for i in 0..len {
+            let bit = (value >> (len - i - 1)) & 1;
+            self.set_symbol(start + i, Symbol(bit as u8));
+        }
+        Ok(())
+    }
+
+    /// Writes `value` as a `width` bit binary number starting at the head, see `write_uint`. Values that do not fit
+    /// into `width` bits are rejected rather than truncated, as are widths beyond 64.
+    pub fn write_binary(&mut self, value: u64, width: usize) -> Result<(), TuringError> {
+        self.write_uint(value, self.head, width)
+    }
+
+    /// Reads the `width` bit binary number written by `write_binary` at position `start`, see `read_uint`.
+    /// Widths beyond 64 fail with `UintTooWide` and non-binary symbols with `NotBinary`.
+    pub fn read_binary(&self, start: usize, width: usize) -> Result<u64, TuringError> {
+        self.read_uint(start, width)
+    }
+
+    /// Lengths of the maximal runs of `Symbol::One` in the observed area, from left to right.
+    pub fn read_unary_blocks(&self) -> Vec<usize> {
+        self.read_observed()
+            .split(|&symbol| symbol != Symbol::One)
+            .filter(|block| !block.is_empty())
+            .map(|block| block.len())
+            .collect()
+    }
+
+    /// Decodes the unary number starting at position `start`: the count of consecutive `Symbol::One` cells
+    /// up to the first other symbol or the end of the tape.
+    pub fn count_ones_run(&self, start: usize) -> usize {
+        (start..self.tape_len())
+            .take_while(|&position| self.get_symbol(position) == Symbol::One)
+            .count()
+    }
+
+    /// Number of `Symbol::One`s on the whole tape, e.g. the productivity of a busy beaver.
+    pub fn count_ones(&self) -> usize {
+        self.count_ones_in(0, self.tape_len())
+    }
+
+    /// Number of `Symbol::One`s among the `len` symbols starting at position `start`.
+    /// On binary tapes whose blank is `Symbol::Zero` whole cells are counted at once.
+    pub fn count_ones_in(&self, start: usize, len: usize) -> usize {
+        if self.symbol_width != 1 || self.blank != Symbol::Zero {
+            return self.read_tape(start, len).into_iter().filter(|&symbol| symbol == Symbol::One).count();
+        }
+
+        let end = (start + len).min(self.tape_len());
+        if start >= end {
+            return 0;
+        }
+        (start / USIZE_BIT_SIZE..=(end - 1) / USIZE_BIT_SIZE)
+            .map(|index| {
+                // keeps the bits of the cell that are within `start..end`, counting MSB-first
+                let first_bit = index * USIZE_BIT_SIZE;
+                let from = start.max(first_bit) - first_bit;
+                let to = end.min(first_bit + USIZE_BIT_SIZE) - first_bit;
+                (self.tape.cell(index).bits() & bit_mask::<usize>(from, to - from)).count_ones() as usize
+            })
+            .sum()
+    }
+
+    // positions of the first and the last non-blank symbols
+    fn written_range(&self) -> Option<(usize, usize)> {
+        let mut written = (0..self.tape_len()).filter(|&position| self.get_symbol(position) != self.blank);
+        let first = written.next()?;
+        Some((first, written.next_back().unwrap_or(first)))
+    }
+
+    // positions of the first and the last symbols of the observed area, i.e. the non-blank part of the tape extended
+    // to the head, both included
+    fn observed_range(&self) -> (usize, usize) {
+        let (first_written, last_written) = self.written_range().unwrap_or((self.head, self.head));
+        (first_written.min(self.head), last_written.max(self.head))
+    }
+
+    pub fn print_tape_observed_area(&self, offset: Option<usize>) {
+        self.fmt_tape_observed_area(&mut std::io::stdout().lock(), offset).expect("failed to print the tape");
+    }
+
+    /// Writes the raw bits of the observed area like `print_tape_observed_area`, followed by a newline, to `output`.
+    pub fn fmt_tape_observed_area(&self, output: &mut dyn Write, offset: Option<usize>) -> std::io::Result<()> {
+        writeln!(output, "{}", self.observed_to_string(offset))
+    }
+
+    /// The raw bits printed by `print_tape_observed_area`: the cells from the first to the last non-zero cell, both
+    /// included, widened by `offset` cells on each side and clamped to the tape. A blank tape is returned whole.
+    pub fn observed_to_string(&self, offset: Option<usize>) -> String {
+        let offset = offset.unwrap_or(0);
+        let last_cell = self.tape.cell_count() - 1;
+        let (first_non_zero, last_non_zero) = self.tape.written_cells().unwrap_or((0, last_cell));
+        let start = first_non_zero.saturating_sub(offset);
+        let end = last_non_zero.saturating_add(offset).min(last_cell);
+
+        (start..=end)
+            .map(|index| format!("{:0width$b}", self.tape.cell(index), width = USIZE_BIT_SIZE))
+            .collect()
+    }
+
+    /// The observed area (the non-blank part of the tape extended to the head) as runs of equal symbols, e.g.
+    /// `0^12 [1^3] 0^5`, with the run under the head in brackets. Meant for logging long tapes.
+    pub fn fmt_tape_rle(&self) -> String {
+        let (start, end) = self.observed_range();
+        let mut runs = Vec::<(Symbol, usize, bool)>::new();
+        for position in start..=end {
+            let symbol = self.get_symbol(position);
+            match runs.last_mut() {
+                Some((last, len, has_head)) if *last == symbol => {
+                    *len += 1;
+                    *has_head |= position == self.head;
+                }
+                _ => runs.push((symbol, 1, position == self.head)),
+            }
+        }
+        runs.iter()
+            .map(|&(symbol, len, has_head)| {
+                let run = format!("{}^{}", self.symbol_char(symbol).unwrap(), len);
+                if has_head { format!("{}{}{}", HEAD_MARKS_PLAIN.0, run, HEAD_MARKS_PLAIN.1) } else { run }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The symbols of the whole tape, without marking the head.
+    pub fn tape_to_string(&self) -> String {
+        self.format_symbols(0, self.tape_len() - 1, ("", ""))
     }
 
+    /// Prints the whole tape with the head highlighted using ANSI escape codes.
     pub fn print_tape(&self) {
-        let binary_str = self.tape
-            .iter()
-            .fold(String::new(), |mut acc, item| {
-                acc.push_str(format!("{:0width$b}", item, width = USIZE_BIT_SIZE).as_str());
-                acc
-            });
-
-        let head_val = self.get_head_value();
-        let binary_str = format!(
-            "{prefix}\x1b[32m\x1b[4m{head_val}\x1b[0m{postfix}",
-            prefix = &binary_str[0..self.head],
-            head_val = head_val as u8,
-            postfix = &binary_str[self.head + 1..],
-        );
-        
-        println!("{}", binary_str);
+        self.fmt_tape(&mut std::io::stdout().lock(), true).expect("failed to print the tape");
+    }
+
+    /// Prints the whole tape like `print_tape` with the head in brackets instead of escape codes, e.g. for logs.
+    pub fn print_tape_plain(&self) {
+        self.fmt_tape(&mut std::io::stdout().lock(), false).expect("failed to print the tape");
+    }
+
+    /// Writes the whole tape, followed by a newline, to `output`. The head is highlighted using ANSI escape codes
+    /// if `colored`, and put in brackets otherwise.
+    pub fn fmt_tape(&self, output: &mut dyn Write, colored: bool) -> std::io::Result<()> {
+        writeln!(output, "{}", self.format_tape(colored))
+    }
+
+    // the whole tape with the head highlighted using ANSI escape codes if `colored`, and in brackets otherwise
+    fn format_tape(&self, colored: bool) -> String {
+        let head_marks = if colored { HEAD_MARKS_COLORED } else { HEAD_MARKS_PLAIN };
+        self.format_symbols(0, self.tape_len() - 1, head_marks)
+    }
+
+    // the symbols from `start` to `end`, both included, with the one under the head wrapped into `head_marks`
+    fn format_symbols(&self, start: usize, end: usize, head_marks: (&str, &str)) -> String {
+        (start..=end)
+            .map(|position| {
+                let symbol = self.symbol_char(self.get_symbol(position)).unwrap();
+                if position == self.head {
+                    format!("{}{}{}", head_marks.0, symbol, head_marks.1)
+                } else {
+                    symbol.to_string()
+                }
+            })
+            .collect()
     }
 
     pub fn tape_len(&self) -> usize {
-        (self.tape.len() * USIZE_BIT_SIZE) as usize
+        self.tape.cell_count() * USIZE_BIT_SIZE / self.symbol_width
     }
 
     pub fn get_head_value(&self) -> Symbol {
-        // 9 <=> 1.0
+        self.get_symbol(self.head)
+    }
+
+    // reads the `symbol_width` bits of the symbol at `position`, a symbol may span two cells
+    fn get_symbol(&self, position: usize) -> Symbol {
+        if self.symbol_width == 1 {
+            return Symbol(self.tape.get_bit(position) as u8 ^ self.blank.0);
+        }
 
-        let cell = &self.tape[self.head / USIZE_BIT_SIZE];
-        let bit_idx = self.head % USIZE_BIT_SIZE;
+        let first_bit = position * self.symbol_width;
+        let stored = (first_bit..first_bit + self.symbol_width).fold(0, |acc, bit| (acc << 1) | self.tape.get_bit(bit));
+        Symbol(stored as u8 ^ self.blank.0)
+    }
+
+    // internal writes only, the public ones check the symbols against the alphabet first
+    fn set_symbol(&mut self, position: usize, symbol: Symbol) {
+        assert!(self.symbol_char(symbol).is_some(), "Symbol `{}` is not part of the alphabet", symbol);
+
+        let stored = (symbol.0 ^ self.blank.0) as usize;
+        if self.symbol_width == 1 {
+            self.write_bit(position, stored);
+            return;
+        }
 
-        let value = get_bit(cell, &bit_idx);
+        let first_bit = position * self.symbol_width;
+        for (i, bit) in (first_bit..first_bit + self.symbol_width).enumerate() {
+            self.write_bit(bit, (stored >> (self.symbol_width - i - 1)) & 1);
+        }
+    }
+
+    fn write_bit(&mut self, bit: usize, value: usize) {
         match value {
-            0 => Symbol::Zero,
-            1 => Symbol::One,
-            _ => panic!("Unexpected bit value: {}", value),
+            0 => self.tape.unset_bit(bit),
+            _ => self.tape.set_bit(bit),
         }
     }
 
     pub fn move_head(&mut self, direction: Direction) {
-        // TODO: if head moves outside tape bounds, reallocate tape with double size
-        self.head = (self.head as isize + direction as isize) as usize;
+        self.move_head_by(direction as isize);
     }
 
-    pub fn set_head_value(&mut self, value: Symbol) {
-        let cell = &mut self.tape[self.head / USIZE_BIT_SIZE];
-        let bit_idx = self.head % USIZE_BIT_SIZE;
+    /// Moves the head by `steps` cells, to the left for negative values, growing the tape as far as the jump needs.
+    pub fn move_head_by(&mut self, steps: isize) {
+        while steps < -(self.head as isize) {
+            self.grow_tape_left();
+        }
+        let target = self.head.checked_add_signed(steps).unwrap();
+        self.ensure_tape_len(target + 1);
+        self.head = target;
 
-        match value {
-            Symbol::Zero => unset_bit(cell, &bit_idx),
-            Symbol::One => set_bit(cell, &bit_idx),
+        let position = self.position();
+        self.visited_range = (self.visited_range.0.min(position), self.visited_range.1.max(position));
+    }
+
+    /// Physical indices of the leftmost and the rightmost cells the head has been on since the machine was created or
+    /// reset, both included, i.e. the window of the tape the computation used. Like `head` they shift whenever the
+    /// tape grows to the left.
+    pub fn visited_range(&self) -> (usize, usize) {
+        let (leftmost, rightmost) = self.visited_range;
+        ((self.origin as i64 + leftmost) as usize, (self.origin as i64 + rightmost) as usize)
+    }
+
+    /// Number of cells between the leftmost and the rightmost position the head has been on since the machine was
+    /// created or reset, both included, i.e. the space the computation used.
+    pub fn space_used(&self) -> usize {
+        (self.visited_range.1 - self.visited_range.0) as usize + 1
+    }
+
+    // doubles the tape by prepending zeroed cells and shifts all positions so the logical contents stay in place,
+    // the number of prepended cells is a multiple of `symbol_width` so that it holds a whole number of symbols
+    fn grow_tape_left(&mut self) {
+        let added_cells = self.tape.cell_count().div_ceil(self.symbol_width) * self.symbol_width;
+        self.tape.prepend_cells(added_cells);
+
+        let shift = added_cells * USIZE_BIT_SIZE / self.symbol_width;
+        self.head += shift;
+        self.origin += shift;
+    }
+
+    fn ensure_tape_len(&mut self, len: usize) {
+        while len > self.tape_len() {
+            self.grow_tape_right();
         }
     }
+
+    // doubles the tape by appending zeroed cells, positions are not affected
+    fn grow_tape_right(&mut self) {
+        self.tape.append_cells(self.tape.cell_count());
+    }
+
+    /// Writes `value` under the head, failing if it is not part of the alphabet.
+    pub fn set_head_value(&mut self, value: Symbol) -> Result<(), TuringError> {
+        self.write_at(self.head, &[value])
+    }
+
+    /// Swaps the symbol under the head between `Symbol::Zero` and `Symbol::One`.
+    /// With a two-symbol alphabet the stored bit is flipped in place.
+    pub fn flip_head_value(&mut self) -> Result<(), TuringError> {
+        if self.symbol_width == 1 && self.alphabet.len() == 2 {
+            self.tape.toggle_bit(self.head);
+            return Ok(());
+        }
+        match self.get_head_value() {
+            Symbol::Zero => self.set_head_value(Symbol::One),
+            Symbol::One => self.set_head_value(Symbol::Zero),
+            symbol => Err(TuringError::NotBinary(symbol)),
+        }
+    }
+
+    /// Toggles the symbol under the head, the same as `flip_head_value`.
+    pub fn toggle_head_value(&mut self) -> Result<(), TuringError> {
+        self.flip_head_value()
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////////////////
 //////////////////////////////////////////////////// TESTS ////////////////////////////////////////////////////
 ///////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-// TODO: add tests
-// #[cfg(test)]
-// mod test {
-//     use super::*;
+#[cfg(test)]
+mod test {
+    use super::*;
 
-//     #[test]
-//     fn new_test() {
-        
-//     }
-// }
\ No newline at end of file
+    #[test]
+    fn named_states_test() {
+        let mut machine = TuringMachine::new();
+        machine.define_named_states(&[(1, "scan_right"), (2, "done")]);
+        machine.set_initial_state(machine.state_by_name("scan_right").unwrap()).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(ProgramState { id: 1 }, Symbol::One, Symbol::One, Direction::Right, State::define(1)),
+            TransitionRule::new(ProgramState { id: 1 }, Symbol::Zero, Symbol::Zero, Direction::Stay, State::define(2)),
+            TransitionRule::new(ProgramState { id: 2 }, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt),
+        ]).unwrap();
+        machine.write_to_tape(&[Symbol::One]).unwrap();
+
+        let mut output = Vec::new();
+        assert!(matches!(machine.run_with_output(&mut output), Ok(State::Halt)));
+
+        let states = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| line.split(':').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(states, vec!["q_scan_right", "q_scan_right", "q_done"]);
+        assert_eq!(machine.state_label(2), Some("done"));
+        assert_eq!(machine.state_by_name("missing"), None);
+    }
+
+    #[test]
+    fn position_is_stable_across_left_growth_test() {
+        let mut machine = TuringMachine::with_alphabet_size(3).unwrap();
+        machine.write_to_tape(&[Symbol::new(2)]).unwrap();
+        let steps = machine.tape_len() * 3;
+
+        for i in 1..=steps {
+            machine.move_head(Direction::Left);
+            assert_eq!(machine.position(), -(i as i64));
+        }
+        assert!(machine.head() < machine.tape_len());
+        for _ in 0..steps {
+            machine.move_head(Direction::Right);
+        }
+
+        assert_eq!(machine.position(), 0);
+        assert_eq!(machine.get_head_value(), Symbol::new(2));
+    }
+
+    #[test]
+    fn move_head_left_from_zero_grows_tape_test() {
+        let mut machine = TuringMachine::new();
+        let initial_tape_len = machine.tape_len();
+
+        // walk left far past the left edge of the initial tape, writing a one on every cell passed
+        let steps = initial_tape_len * 2;
+        let states = (1..=steps as ProgramStateId + 1).map(|id| ProgramState { id }).collect::<Vec<_>>();
+        machine.define_states(&states);
+        machine.set_initial_state(1).unwrap();
+
+        let mut rules = states
+            .windows(2)
+            .map(|pair| TransitionRule::new(pair[0], Symbol::Zero, Symbol::One, Direction::Left, State::ProgramState(pair[1])))
+            .collect::<Vec<_>>();
+        rules.push(TransitionRule::new(states[steps], Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination));
+        machine.define_transition_table(&rules).unwrap();
+
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+        assert!(machine.tape_len() > initial_tape_len);
+        assert!(matches!(machine.get_head_value(), Symbol::Zero));
+
+        for _ in 0..steps {
+            machine.move_head(Direction::Right);
+            assert!(matches!(machine.get_head_value(), Symbol::One));
+        }
+        machine.move_head(Direction::Right);
+        assert!(matches!(machine.get_head_value(), Symbol::Zero));
+    }
+
+    #[test]
+    fn move_head_left_shifts_head_by_whole_cells_test() {
+        let mut machine = TuringMachine::new();
+        machine.head = 0;
+        machine.set_head_value(Symbol::One).unwrap();
+
+        machine.move_head(Direction::Left);
+
+        assert_eq!(machine.tape_len(), DEFAULT_TAPE_SIZE * 2 * USIZE_BIT_SIZE);
+        assert_eq!(machine.head(), DEFAULT_TAPE_SIZE * USIZE_BIT_SIZE - 1);
+        machine.move_head(Direction::Right);
+        assert!(matches!(machine.get_head_value(), Symbol::One));
+    }
+
+    #[test]
+    fn flip_head_value_test() {
+        let mut machine = TuringMachine::new();
+        machine.flip_head_value().unwrap();
+        assert_eq!(machine.get_head_value(), Symbol::One);
+        machine.flip_head_value().unwrap();
+        assert_eq!(machine.get_head_value(), Symbol::Zero);
+
+        // walk over the boundary between the first two cells, flipping every symbol on the way
+        machine.head = USIZE_BIT_SIZE - 2;
+        for _ in 0..4 {
+            machine.flip_head_value().unwrap();
+            machine.move_head(Direction::Right);
+        }
+        assert_eq!(machine.read_tape(USIZE_BIT_SIZE - 3, 6), Symbol::vec_from_numbers(&[0, 1, 1, 1, 1, 0]));
+        assert_eq!(machine.tape[0], BitCell::new(0b11));
+        assert_eq!(machine.tape[1], BitCell::new(0b11 << (USIZE_BIT_SIZE - 2)));
+
+        let mut machine = TuringMachine::with_blank();
+        assert_eq!(machine.flip_head_value(), Err(TuringError::NotBinary(Symbol::new(2))));
+        machine.set_head_value(Symbol::One).unwrap();
+        machine.flip_head_value().unwrap();
+        assert_eq!(machine.get_head_value(), Symbol::Zero);
+    }
+
+    #[test]
+    fn toggle_head_value_twice_restores_symbol_test() {
+        let mut machine = example_machine();
+        let tape = machine.tape.clone();
+        machine.toggle_head_value().unwrap();
+        assert_eq!(machine.get_head_value(), Symbol::Zero);
+        machine.toggle_head_value().unwrap();
+        assert_eq!(machine.get_head_value(), Symbol::One);
+        assert_eq!(machine.tape, tape);
+        assert_eq!(TuringMachine::with_blank().toggle_head_value(), Err(TuringError::NotBinary(Symbol::new(2))));
+    }
+
+    #[test]
+    fn sparse_tape_stores_only_written_cells_test() {
+        // sweeps right forever, writing a one every `PERIOD` cells
+        const PERIOD: usize = 1024;
+        fn sweep<T: TapeStorage>(mut machine: TuringMachine<T>) -> TuringMachine<T> {
+            let states = (1..=PERIOD as ProgramStateId).map(|id| ProgramState { id }).collect::<Vec<_>>();
+            machine.define_states(&states);
+            machine.set_initial_state(1).unwrap();
+            let mut rules = states
+                .windows(2)
+                .map(|pair| TransitionRule::new(pair[0], Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(pair[1])))
+                .collect::<Vec<_>>();
+            rules[0].new_symbol = Symbol::One;
+            rules.push(TransitionRule::new(states[PERIOD - 1], Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(states[0])));
+            machine.define_transition_table(&rules).unwrap();
+
+            let steps = 16 * PERIOD as u64;
+            assert_eq!(machine.run_with_limit(steps), Err(TuringError::StepLimitExceeded(steps)));
+            machine
+        }
+
+        let sparse = sweep(TuringMachine::sparse());
+        let dense = sweep(TuringMachine::new());
+
+        assert_eq!(sparse.tape.stored_cells(), 16);
+        assert!(dense.tape.len() >= 16 * PERIOD / USIZE_BIT_SIZE);
+        assert_eq!(sparse.tape_len(), dense.tape_len());
+        assert_eq!(sparse.head(), dense.head());
+        assert_eq!(sparse.read_observed(), dense.read_observed());
+        assert_eq!(sparse.read_unary_blocks(), vec![1; 16]);
+    }
+
+    #[test]
+    fn move_head_by_jumps_past_tape_edges_test() {
+        let mut machine = TuringMachine::new();
+        machine.set_head_value(Symbol::One).unwrap();
+        let initial_tape_len = machine.tape_len();
+
+        machine.move_head_by(-10 * initial_tape_len as isize);
+        assert_eq!(machine.position(), -10 * initial_tape_len as i64);
+        assert_eq!(machine.get_head_value(), Symbol::Zero);
+        machine.move_head_by(25 * initial_tape_len as isize);
+        assert_eq!(machine.position(), 15 * initial_tape_len as i64);
+        assert!(machine.head() < machine.tape_len());
+
+        machine.move_head_by(-15 * initial_tape_len as isize);
+        assert_eq!(machine.position(), 0);
+        assert_eq!(machine.get_head_value(), Symbol::One);
+        machine.move_head_by(0);
+        assert_eq!(machine.position(), 0);
+    }
+
+    #[test]
+    fn visited_range_test() {
+        // walks two cells left of the input, then right past its end
+        let mut machine = TuringMachine::from_program("
+            start: q1
+            q1 1 -> 1 L q1
+            q1 0 -> 0 L q2
+            q2 0 -> 0 R q3
+            q3 0 -> 0 R q4
+            q4 1 -> 1 R q4
+            q4 0 -> 0 S halt
+        ").unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1])).unwrap();
+        let start = machine.head();
+        assert_eq!(machine.visited_range(), (start, start));
+
+        assert_eq!(machine.run(), Ok(State::Halt));
+        assert_eq!(machine.visited_range(), (start - 2, start + 2));
+        assert_eq!(machine.visited_range().1, machine.head());
+        assert_eq!(machine.space_used(), 5);
+
+        // growing the tape to the left shifts the range along with the head
+        machine.move_head_by(-(machine.head() as isize) - 1);
+        assert_eq!(machine.visited_range().0, machine.head());
+        assert_eq!(machine.visited_range().1, (machine.origin as i64 + 2) as usize);
+
+        machine.reset();
+        assert_eq!(machine.visited_range(), (machine.head(), machine.head()));
+    }
+
+    #[test]
+    fn space_used_test() {
+        let mut machine = example_machine();
+        assert_eq!(machine.space_used(), 1);
+        machine.run().unwrap();
+        // the head only walks over the first block and the zero after it
+        assert_eq!(machine.space_used(), 5);
+
+        machine.reset();
+        assert_eq!(machine.space_used(), 1);
+        machine.move_head_by(-3);
+        machine.move_head_by(-500);
+        machine.move_head_by(5);
+        assert_eq!(machine.space_used(), 504);
+    }
+
+    #[test]
+    fn move_head_right_past_tape_end_grows_tape_test() {
+        let mut machine = TuringMachine::new();
+        let initial_tape_len = machine.tape_len();
+
+        // walk right past the right edge of the initial tape, expecting blank cells all the way
+        let steps = initial_tape_len;
+        let states = (1..=steps as ProgramStateId + 1).map(|id| ProgramState { id }).collect::<Vec<_>>();
+        machine.define_states(&states);
+        machine.set_initial_state(1).unwrap();
+
+        let mut rules = states
+            .windows(2)
+            .map(|pair| TransitionRule::new(pair[0], Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(pair[1])))
+            .collect::<Vec<_>>();
+        rules.push(TransitionRule::new(states[steps], Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination));
+        machine.define_transition_table(&rules).unwrap();
+
+        let start = machine.head();
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+        assert!(machine.tape_len() > initial_tape_len);
+        assert_eq!(machine.head(), start + steps);
+        assert!(matches!(machine.get_head_value(), Symbol::Zero));
+    }
+
+    fn read_from_head(machine: &mut TuringMachine, len: usize) -> Vec<Symbol> {
+        let start = machine.head();
+        let symbols = (0..len)
+            .map(|_| {
+                let symbol = machine.get_head_value();
+                machine.move_head(Direction::Right);
+                symbol
+            })
+            .collect();
+        while machine.head() > start {
+            machine.move_head(Direction::Left);
+        }
+        symbols
+    }
+
+    #[test]
+    fn write_to_tape_fills_whole_tape_length_test() {
+        let mut machine = TuringMachine::new();
+        let cells = (0..machine.tape_len()).map(|i| [Symbol::One, Symbol::Zero][i % 2]).collect::<Vec<_>>();
+
+        machine.write_to_tape(&cells).unwrap();
+
+        assert!(machine.tape_len() >= machine.head() + cells.len());
+        assert!(read_from_head(&mut machine, cells.len()) == cells);
+    }
+
+    #[test]
+    fn write_to_tape_from_unaligned_head_test() {
+        // the head sits 3 bits before a cell boundary, so the input straddles two cells
+        let mut machine = TuringMachine::new();
+        machine.head = USIZE_BIT_SIZE - 3;
+        let cells = Symbol::vec_from_numbers(&[1, 0, 1, 1, 0, 1]);
+
+        machine.write_to_tape(&cells).unwrap();
+
+        assert_eq!(machine.tape[0], BitCell::new(0b101));
+        assert_eq!(machine.tape[1], BitCell::new(0b101 << (USIZE_BIT_SIZE - 3)));
+        assert_eq!(machine.read_tape(USIZE_BIT_SIZE - 4, 8), Symbol::vec_from_numbers(&[0, 1, 0, 1, 1, 0, 1, 0]));
+        assert_eq!(read_from_head(&mut machine, cells.len()), cells);
+    }
+
+    #[test]
+    fn write_to_tape_grows_tape_for_oversized_input_test() {
+        let mut machine = TuringMachine::new();
+        let initial_tape_len = machine.tape_len();
+        let cells = vec![Symbol::One; initial_tape_len + 1];
+
+        machine.write_to_tape(&cells).unwrap();
+
+        assert!(machine.tape_len() > initial_tape_len);
+        assert!(read_from_head(&mut machine, cells.len()) == cells);
+    }
+
+    #[test]
+    fn move_head_left_past_edge_keeps_written_symbols_test() {
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 0, 1])).unwrap();
+
+        let walk = machine.head() + 3 * machine.tape_len();
+        for _ in 0..walk {
+            machine.move_head(Direction::Left);
+        }
+        for _ in 0..walk {
+            machine.move_head(Direction::Right);
+        }
+
+        assert!(read_from_head(&mut machine, 3) == Symbol::vec_from_numbers(&[1, 0, 1]));
+    }
+
+    #[test]
+    fn with_alphabet_validation_test() {
+        assert_eq!(TuringMachine::with_alphabet(&[], '_').err(), Some(TuringError::AlphabetSize { max: MAX_ALPHABET_SIZE }));
+        assert_eq!(TuringMachine::with_alphabet(&['a', 'b'], '_').err(), Some(TuringError::BlankNotInAlphabet('_')));
+        assert_eq!(TuringMachine::with_alphabet(&['a', 'b', 'a'], 'a').err(), Some(TuringError::DuplicateSymbol('a')));
+
+        let machine = TuringMachine::with_alphabet(&['a', '_', 'b'], '_').unwrap();
+        assert!(machine.blank() == Symbol::new(1));
+        assert!(machine.symbol('b') == Ok(Symbol::new(2)));
+        assert_eq!(machine.symbol('c'), Err(TuringError::UnknownSymbol('c')));
+        assert_eq!(machine.symbol_char(Symbol::new(0)), Some('a'));
+        assert!(machine.get_head_value() == machine.blank());
+    }
+
+    #[test]
+    fn with_alphabet_packs_multi_bit_symbols_test() {
+        // 5 symbols take 3 bits each, so symbols straddle the boundaries between cells
+        let mut machine = TuringMachine::with_alphabet(&['_', 'a', 'b', 'c', 'd'], '_').unwrap();
+        let input = (0..2 * USIZE_BIT_SIZE).map(|i| Symbol::new((i % 4) as u8 + 1)).collect::<Vec<_>>();
+
+        machine.write_to_tape(&input).unwrap();
+
+        assert!(read_from_head(&mut machine, input.len()) == input);
+        for _ in 0..input.len() {
+            machine.move_head(Direction::Right);
+        }
+        assert!(machine.get_head_value() == machine.blank());
+    }
+
+    #[test]
+    fn with_alphabet_grows_tape_left_test() {
+        let mut machine = TuringMachine::with_alphabet(&['_', 'a', 'b', 'c', 'd'], '_').unwrap();
+        let input = vec![machine.symbol('d').unwrap(), machine.symbol('a').unwrap()];
+        machine.write_to_tape(&input).unwrap();
+
+        let walk = machine.head() + 1;
+        for _ in 0..walk {
+            machine.move_head(Direction::Left);
+        }
+        assert!(machine.get_head_value() == machine.blank());
+        for _ in 0..walk {
+            machine.move_head(Direction::Right);
+        }
+
+        assert!(read_from_head(&mut machine, input.len()) == input);
+    }
+
+    #[test]
+    fn with_alphabet_runs_ternary_machine_test() {
+        // swaps every `a` and `b` until the first blank
+        let mut machine = TuringMachine::with_alphabet(&['_', 'a', 'b'], '_').unwrap();
+        let [blank, a, b] = [Symbol::new(0), Symbol::new(1), Symbol::new(2)];
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, a, b, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q1, b, a, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q1, blank, blank, Direction::Stay, State::Termination),
+        ]).unwrap();
+        machine.write_to_tape(&[a, a, b, a]).unwrap();
+        let start = machine.head();
+
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+        while machine.head() > start {
+            machine.move_head(Direction::Left);
+        }
+        assert!(read_from_head(&mut machine, 5) == vec![b, b, a, b, blank]);
+    }
+
+    #[test]
+    fn define_transition_table_rejects_symbols_outside_alphabet_test() {
+        let mut machine = TuringMachine::new();
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+
+        let result = machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::new(2), Direction::Stay, State::Halt),
+        ]);
+
+        assert_eq!(result, Err(TuringError::SymbolNotInAlphabet(Symbol::new(2))));
+    }
+
+    #[test]
+    fn writes_reject_symbols_outside_alphabet_test() {
+        let mut machine = TuringMachine::new();
+        let head = machine.head();
+        let error = Err(TuringError::SymbolNotInAlphabet(Symbol::new(5)));
+
+        assert_eq!(machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 5])), error);
+        assert_eq!(machine.write_at(head + 3, &[Symbol::new(5)]), error);
+        assert_eq!(machine.set_head_value(Symbol::new(5)), error);
+        assert_eq!(machine.reset_with_input(&[Symbol::new(5)]), error);
+        assert_eq!(machine.read_observed(), vec![]);
+
+        let mut blank_only = TuringMachine::with_alphabet_size(1).unwrap();
+        assert_eq!(blank_only.write_uint(1, 0, 1), Err(TuringError::SymbolNotInAlphabet(Symbol::One)));
+        assert_eq!(blank_only.write_uint(0, 0, 1), Ok(()));
+    }
+
+    // the machine from `main.rs`
+    fn example_machine() -> TuringMachine {
+        let mut machine = TuringMachine::new();
+        let [q1, q2, q3, q4] = [1, 2, 3, 4].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2, q3, q4]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination),
+            TransitionRule::new(q1, Symbol::One, Symbol::Zero, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q2, Symbol::Zero, Symbol::One, Direction::Left, State::ProgramState(q3)),
+            TransitionRule::new(q2, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q3, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q4)),
+            TransitionRule::new(q3, Symbol::One, Symbol::One, Direction::Left, State::ProgramState(q3)),
+            TransitionRule::new(q4, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt),
+            TransitionRule::new(q4, Symbol::One, Symbol::Zero, Direction::Right, State::Termination),
+        ]).unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1])).unwrap();
+        machine
+    }
+
+    #[test]
+    fn reset_test() {
+        let mut machine = example_machine();
+        let head = machine.head();
+        machine.run().unwrap();
+        // run far enough left to grow the tape
+        machine.head = 0;
+        machine.move_head(Direction::Left);
+
+        machine.reset();
+        assert_eq!(machine.head(), head);
+        assert_eq!(machine.position(), 0);
+        assert_eq!(machine.current_state(), Some(State::define(1)));
+        assert!(machine.read_observed().is_empty());
+        assert_eq!(machine.tape_len(), TuringMachine::new().tape_len());
+
+        machine.reset_with_input(&Symbol::vec_from_numbers(&[1, 1, 0, 1, 1])).unwrap();
+        assert_eq!(machine.run(), Ok(State::Termination));
+        assert_eq!(machine.read_unary_blocks(), vec![3]);
+
+        // a reset machine behaves exactly like a freshly built one
+        machine.reset();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1])).unwrap();
+        let mut fresh = example_machine();
+        assert_eq!(machine.run_traced(), fresh.run_traced());
+        assert_eq!(machine.steps(), fresh.steps());
+        assert_eq!(machine.read_unary_blocks(), vec![6]);
+
+        let mut machine = TuringMachine::with_blank();
+        machine.set_head_value(Symbol::One).unwrap();
+        machine.reset();
+        assert_eq!(machine.get_head_value(), machine.blank());
+        assert_eq!(machine.current_state(), None);
+    }
+
+    #[test]
+    fn step_matches_run_test() {
+        let mut stepped = example_machine();
+        let mut whole = example_machine();
+
+        let mut steps = 0;
+        let final_state = loop {
+            steps += 1;
+            match stepped.step().unwrap() {
+                State::ProgramState(_) => continue,
+                state => break state,
+            }
+        };
+
+        assert!(matches!(final_state, State::Termination));
+        assert!(matches!(whole.run(), Ok(State::Termination)));
+        assert_eq!(steps, 10);
+        assert_eq!(stepped.head(), whole.head());
+        assert_eq!(stepped.tape, whole.tape);
+        assert!(matches!(stepped.step(), Ok(State::Termination)));
+    }
+
+    #[test]
+    fn steps_counts_applied_transitions_test() {
+        let mut machine = example_machine();
+        assert_eq!(machine.steps(), 0);
+        machine.step().unwrap();
+        assert_eq!(machine.steps(), 1);
+        machine.run().unwrap();
+        assert_eq!(machine.steps(), 10);
+        machine.step().unwrap();
+        assert_eq!(machine.steps(), 10);
+        machine.reset();
+        assert_eq!(machine.steps(), 0);
+
+        // q1 halts on the zero after the input, as there is no rule for it
+        let mut machine = TuringMachine::new();
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(q1))]).unwrap();
+        machine.write_to_tape(&Symbol::unary(3)).unwrap();
+        assert_eq!(machine.run(), Ok(State::Halt));
+        assert_eq!(machine.steps(), 3);
+
+        // a report counts the steps of its own run, the machine all steps since it was reset
+        let mut machine = example_machine();
+        machine.step().unwrap();
+        let report = machine.run_reporting().unwrap();
+        assert_eq!(report.steps, 9);
+        assert_eq!(machine.steps(), 10);
+    }
+
+    #[test]
+    fn clone_mid_run_diverges_test() {
+        let mut machine = example_machine();
+        for _ in 0..4 {
+            machine.step().unwrap();
+        }
+        let mut copy = machine.clone();
+        // the copy fills the gap between the two blocks, so it only sees a single block
+        copy.set_head_value(Symbol::One).unwrap();
+
+        assert_eq!(machine.run(), Ok(State::Termination));
+        assert_eq!(copy.run(), Ok(State::Termination));
+        assert_eq!(machine.read_unary_blocks(), vec![6]);
+        assert_eq!(copy.read_unary_blocks(), vec![7]);
+        assert_ne!(machine.steps(), copy.steps());
+    }
+
+    #[test]
+    fn step_without_initial_state_test() {
+        let mut machine = TuringMachine::new();
+        assert_eq!(machine.step(), Err(TuringError::InitialStateNotSet));
+        assert_eq!(machine.run(), Err(TuringError::InitialStateNotSet));
+        assert_eq!(machine.set_initial_state(1), Err(TuringError::StateNotDefined(1)));
+        assert_eq!(
+            machine.define_transition_table(&[
+                TransitionRule::new(ProgramState { id: 1 }, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt),
+            ]),
+            Err(TuringError::StateNotDefined(1)),
+        );
+    }
+
+    #[test]
+    fn nondeterministic_rules_are_rejected_by_default_test() {
+        let mut machine = TuringMachine::new();
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+
+        let result = machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt),
+            TransitionRule::new(q1, Symbol::Zero, Symbol::One, Direction::Stay, State::Termination),
+        ]);
+
+        assert_eq!(result, Err(TuringError::DuplicateRule { state: 1, label: None, symbol: Symbol::Zero }));
+    }
+
+    #[test]
+    fn run_nondeterministic_finds_terminating_branch_test() {
+        // q1 either keeps walking right forever or writes a one and moves to q2,
+        // which terminates only when it reads the one written two cells before
+        let mut machine = TuringMachine::new();
+        machine.set_nondeterministic(true);
+        let [q1, q2, q3] = [1, 2, 3].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2, q3]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q1, Symbol::Zero, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q2, Symbol::Zero, Symbol::Zero, Direction::Left, State::ProgramState(q3)),
+            TransitionRule::new(q3, Symbol::One, Symbol::One, Direction::Stay, State::Termination),
+        ]).unwrap();
+
+        assert_eq!(machine.get_transition_rules(&q1.id, &Symbol::Zero).len(), 2);
+        assert_eq!(machine.run_nondeterministic(), Ok(true));
+    }
+
+    #[test]
+    fn run_search_accepts_palindromes_test() {
+        // erases the leftmost symbol and either guesses that it was the middle one, in which case nothing may be
+        // left, or walks to the right end to erase the same symbol there and starts over
+        let palindrome_machine = |input: &str| {
+            let mut machine = TuringMachine::with_alphabet(&['_', 'a', 'b'], '_').unwrap();
+            machine.set_nondeterministic(true);
+            let [blank, a, b] = ['_', 'a', 'b'].map(|c| machine.symbol(c).unwrap());
+            let [q1, q2a, q2b, q3a, q3b, q4, q5] = [1, 2, 3, 4, 5, 6, 7].map(|id| ProgramState { id });
+            machine.define_states(&[q1, q2a, q2b, q3a, q3b, q4, q5]);
+            machine.set_initial_state(q1.id).unwrap();
+            machine.define_transition_table(&[
+                TransitionRule::new(q1, blank, blank, Direction::Stay, State::Accept),
+                TransitionRule::new(q1, a, blank, Direction::Right, State::ProgramState(q2a)),
+                TransitionRule::new(q1, a, blank, Direction::Right, State::ProgramState(q5)),
+                TransitionRule::new(q1, b, blank, Direction::Right, State::ProgramState(q2b)),
+                TransitionRule::new(q1, b, blank, Direction::Right, State::ProgramState(q5)),
+                TransitionRule::new(q2a, a, a, Direction::Right, State::ProgramState(q2a)),
+                TransitionRule::new(q2a, b, b, Direction::Right, State::ProgramState(q2a)),
+                TransitionRule::new(q2a, blank, blank, Direction::Left, State::ProgramState(q3a)),
+                TransitionRule::new(q2b, a, a, Direction::Right, State::ProgramState(q2b)),
+                TransitionRule::new(q2b, b, b, Direction::Right, State::ProgramState(q2b)),
+                TransitionRule::new(q2b, blank, blank, Direction::Left, State::ProgramState(q3b)),
+                TransitionRule::new(q3a, a, blank, Direction::Left, State::ProgramState(q4)),
+                TransitionRule::new(q3a, b, b, Direction::Stay, State::Reject),
+                TransitionRule::new(q3b, b, blank, Direction::Left, State::ProgramState(q4)),
+                TransitionRule::new(q3b, a, a, Direction::Stay, State::Reject),
+                TransitionRule::new(q4, a, a, Direction::Left, State::ProgramState(q4)),
+                TransitionRule::new(q4, b, b, Direction::Left, State::ProgramState(q4)),
+                TransitionRule::new(q4, blank, blank, Direction::Right, State::ProgramState(q1)),
+                TransitionRule::new(q5, blank, blank, Direction::Stay, State::Accept),
+            ]).unwrap();
+            let input = input.chars().map(|c| machine.symbol(c).unwrap()).collect::<Vec<_>>();
+            machine.write_to_tape(&input).unwrap();
+            machine
+        };
+
+        for input in ["", "a", "abba", "aba", "babbab", "abbba"] {
+            assert_eq!(palindrome_machine(input).run_search(100), Ok(State::Accept), "{}", input);
+        }
+        for input in ["ab", "abab", "aab", "abbaa"] {
+            assert_eq!(palindrome_machine(input).run_search(100), Ok(State::Reject), "{}", input);
+        }
+        // the search gives up on branches that are deeper than `max_depth`
+        assert_eq!(palindrome_machine("abba").run_search(5), Ok(State::Reject));
+
+        let machine = palindrome_machine("aba");
+        let head = machine.head();
+        machine.run_search(100).unwrap();
+        assert_eq!(machine.head(), head);
+        assert_eq!(machine.current_state(), Some(State::define(1)));
+    }
+
+    #[test]
+    fn run_nondeterministic_without_terminating_branch_test() {
+        let mut machine = TuringMachine::new();
+        machine.set_nondeterministic(true);
+        let [q1, q2] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt),
+            TransitionRule::new(q2, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q2)),
+        ]).unwrap();
+
+        assert_eq!(machine.run_nondeterministic(), Ok(false));
+    }
+
+    #[test]
+    fn run_with_limit_test() {
+        assert!(matches!(example_machine().run_with_limit(10), Ok(State::Termination)));
+
+        let mut machine = example_machine();
+        assert_eq!(
+            machine.run_with_limit(9).err(),
+            Some(TuringError::StepLimitExceeded(9)),
+        );
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+    }
+
+    #[test]
+    fn execute_does_not_mutate_machine_test() {
+        let machine = example_machine();
+        let tape = machine.read_observed();
+
+        let report = machine.execute(&Symbol::vec_from_numbers(&[1, 1, 0, 1, 1])).unwrap();
+        assert!(matches!(report.final_state, State::Termination));
+        let again = machine.execute(&Symbol::vec_from_numbers(&[1, 1, 0, 1, 1])).unwrap();
+        assert_eq!(again.steps, report.steps);
+        assert!(machine.execute(&Symbol::vec_from_numbers(&[1, 1, 1, 0, 1])).unwrap().steps > report.steps);
+
+        assert_eq!(machine.read_observed(), tape);
+        assert_eq!(machine.current_state(), Some(State::define(1)));
+        assert_eq!(TuringMachine::new().execute(&[]).err(), Some(TuringError::InitialStateNotSet));
+    }
+
+    #[test]
+    fn run_with_observer_test() {
+        let mut machine = example_machine();
+        let start = machine.head();
+        let mut observed = Vec::new();
+
+        // breaks as soon as the machine is about to enter q3
+        let state = machine.run_with_observer(|machine, rule| {
+            observed.push((machine.current_state().unwrap(), machine.head() - start, rule.new_symbol));
+            !matches!(rule.to_state, State::ProgramState(ProgramState { id: 3 }))
+        });
+
+        assert_eq!(state, Ok(State::define(2)));
+        assert_eq!(observed.len(), 5);
+        assert_eq!(observed[0], (State::define(1), 0, Symbol::Zero));
+        assert_eq!(observed[4], (State::define(2), 4, Symbol::One));
+        assert_eq!(machine.steps(), 4);
+
+        assert_eq!(machine.run_with_observer(|_, _| true), Ok(State::Termination));
+        assert_eq!(machine.read_unary_blocks(), vec![6]);
+    }
+
+    #[test]
+    fn behaves_like_test() {
+        let inputs = [Symbol::vec_from_numbers(&[1, 0, 1]), vec![], Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1])];
+        let machine = example_machine();
+        let mut sparse = TuringMachine::sparse();
+        sparse.define_states(&[1, 2, 3, 4].map(|id| ProgramState { id }));
+        sparse.set_initial_state(1).unwrap();
+        let rules = machine.transition_table.values().flat_map(|rules| rules.values().flatten().copied()).collect::<Vec<_>>();
+        sparse.define_transition_table(&rules).unwrap();
+        assert!(machine.behaves_like(&sparse, &inputs, 100));
+
+        // the same machine, except that q1 halts on an empty input where the example terminates
+        let mut changed = example_machine();
+        changed.transition_table.get_mut(&1).unwrap().get_mut(&Symbol::Zero).unwrap()[0].to_state = State::Halt;
+        assert!(!machine.behaves_like(&changed, &inputs, 100));
+        assert_eq!(machine.first_divergence(&changed, &inputs, 100), Some(1));
+        assert_eq!(machine.first_divergence(&changed, &inputs[2..], 100), None);
+        assert_eq!(machine.read_observed(), example_machine().read_observed());
+    }
+
+    #[test]
+    fn run_reporting_test() {
+        let report = example_machine().run_reporting().unwrap();
+
+        assert!(matches!(report.final_state, State::Termination));
+        assert_eq!(report.steps, 10);
+        assert_eq!(report.head_position, example_machine().head() + 2);
+        assert_eq!(report.visited_states, vec![1, 2, 2, 2, 2, 3, 3, 3, 3, 4]);
+    }
+
+    #[test]
+    fn run_with_limit_stops_never_halting_machine_test() {
+        // a two-state busy beaver whose halting rule is replaced by another step, so it sweeps on forever
+        let mut machine = TuringMachine::new();
+        let [a, b] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[a, b]);
+        machine.set_initial_state(a.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(a, Symbol::Zero, Symbol::One, Direction::Right, State::ProgramState(b)),
+            TransitionRule::new(a, Symbol::One, Symbol::One, Direction::Left, State::ProgramState(b)),
+            TransitionRule::new(b, Symbol::Zero, Symbol::One, Direction::Left, State::ProgramState(a)),
+            TransitionRule::new(b, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(b)),
+        ]).unwrap();
+
+        let result = machine.run_with_limit(1_000);
+
+        assert_eq!(result.err(), Some(TuringError::StepLimitExceeded(1000)));
+    }
+
+    #[test]
+    fn run_with_output_writes_trace_test() {
+        let mut machine = example_machine();
+        let mut output = Vec::<u8>::new();
+
+        assert!(matches!(machine.run_with_output(&mut output), Ok(State::Termination)));
+
+        let output = String::from_utf8(output).unwrap();
+        let states = output.lines().map(|line| line.split(':').next().unwrap()).collect::<Vec<_>>();
+        assert_eq!(states, vec!["q1", "q2", "q2", "q2", "q2", "q3", "q3", "q3", "q3", "q4"]);
+        assert!(output.lines().all(|line| line.contains("\x1b[32m\x1b[4m")));
+    }
+
+    #[test]
+    fn run_traced_test() {
+        let mut machine = example_machine();
+        let start = machine.head();
+
+        let (final_state, trace) = machine.run_traced().unwrap();
+
+        assert!(matches!(final_state, State::Termination));
+        let (l, r) = (Direction::Left, Direction::Right);
+        let expected = [
+            (1, 0, 1, 0, r), (2, 1, 1, 1, r), (2, 2, 1, 1, r), (2, 3, 1, 1, r), (2, 4, 0, 1, l),
+            (3, 3, 1, 1, l), (3, 2, 1, 1, l), (3, 1, 1, 1, l), (3, 0, 0, 0, r), (4, 1, 1, 0, r),
+        ].map(|(state_id, offset, read, written, direction)| TraceStep {
+            state_id,
+            read: Symbol::new(read),
+            written: Symbol::new(written),
+            direction,
+            head: start + offset,
+        });
+        assert_eq!(trace, expected);
+        assert_eq!(trace.len() as u64, example_machine().run_reporting().unwrap().steps);
+    }
+
+    #[test]
+    fn detect_loop_test() {
+        // q1 and q2 bounce the head between two cells forever
+        let mut machine = TuringMachine::new();
+        let [q1, q2] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q2, Symbol::Zero, Symbol::Zero, Direction::Left, State::ProgramState(q1)),
+        ]).unwrap();
+
+        assert_eq!(machine.clone().detect_loop(1), None);
+        assert_eq!(machine.detect_loop(100), Some(2));
+        assert_eq!(example_machine().detect_loop(100), None);
+    }
+
+    #[test]
+    fn current_state_follows_steps_test() {
+        assert!(TuringMachine::new().current_state().is_none());
+
+        let mut machine = example_machine();
+        assert!(matches!(machine.current_state(), Some(State::ProgramState(ProgramState { id: 1 }))));
+        machine.step().unwrap();
+        assert!(matches!(machine.current_state(), Some(State::ProgramState(ProgramState { id: 2 }))));
+        machine.run().unwrap();
+        assert!(matches!(machine.current_state(), Some(State::Termination)));
+    }
+
+    #[test]
+    fn outcome_test() {
+        let mut machine = example_machine();
+        assert_eq!(machine.outcome(), None);
+        assert_eq!(machine.run_reporting().unwrap().outcome, Outcome::Terminated);
+        assert_eq!(machine.outcome(), Some(Outcome::Terminated));
+
+        // q1 halts explicitly on a one and finds no rule for a zero
+        let mut machine = TuringMachine::new();
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Stay, State::Halt)]).unwrap();
+        assert_eq!(machine.run(), Ok(State::Halt));
+        assert_eq!(machine.outcome(), Some(Outcome::HaltedNoRule));
+        machine.reset_with_input(&[Symbol::One]).unwrap();
+        assert_eq!(machine.outcome(), None);
+        let report = machine.run_reporting().unwrap();
+        assert_eq!((report.final_state, report.outcome), (State::Halt, Outcome::Halted));
+
+        let mut machine = TuringMachine::new();
+        machine.define_states(&[q1]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Stay, State::Accept),
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Reject),
+        ]).unwrap();
+        assert_eq!(machine.execute(&[Symbol::One]).unwrap().outcome, Outcome::Accepted);
+        assert_eq!(machine.execute(&[Symbol::Zero]).unwrap().outcome, Outcome::Rejected);
+    }
+
+    #[test]
+    fn accepts_test() {
+        // accepts the words over {0, 1} ending with a one: q2 is entered right after reading a one
+        let mut machine = TuringMachine::with_blank();
+        let blank = machine.blank();
+        let [q1, q2] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q2, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q2, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q1, blank, blank, Direction::Stay, State::Halt),
+        ]).unwrap();
+        assert_eq!(machine.set_accepting_states(&[2, 3]), Err(TuringError::StateNotDefined(3)));
+        machine.set_accepting_states(&[q2.id]).unwrap();
+
+        assert_eq!(machine.accepts(&Symbol::vec_from_numbers(&[0, 1, 1])), Ok(true));
+        assert_eq!(machine.accepts(&Symbol::vec_from_numbers(&[1])), Ok(true));
+        assert_eq!(machine.accepts(&Symbol::vec_from_numbers(&[1, 1, 0])), Ok(false));
+        assert_eq!(machine.accepts(&[]), Ok(false));
+
+        // an explicit halt never accepts, even from an accepting state
+        machine.set_accepting_states(&[q1.id]).unwrap();
+        assert_eq!(machine.accepts(&Symbol::vec_from_numbers(&[0])), Ok(false));
+    }
+
+    #[test]
+    fn state_without_rules_halts_test() {
+        let mut machine = TuringMachine::new();
+        let [q1, q2] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::One, Direction::Right, State::ProgramState(q2)),
+        ]).unwrap();
+
+        assert!(machine.get_transition_rule(&q2.id, &Symbol::Zero).is_none());
+        assert!(machine.get_transition_rules(&q2.id, &Symbol::One).is_empty());
+        assert_eq!(machine.run_nondeterministic(), Ok(false));
+        assert_eq!(machine.clone().run_detect_loop(10), Ok(State::Halt));
+        assert_eq!(machine.clone().run_with_limit(1), Ok(State::Halt));
+        assert!(matches!(machine.run(), Ok(State::Halt)));
+        assert_eq!(machine.step(), Ok(State::Halt));
+    }
+
+    #[test]
+    fn validate_completeness_test() {
+        assert_eq!(example_machine().validate_completeness(), Ok(()));
+        assert_eq!(TuringMachine::new().validate_completeness(), Err(vec![TuringError::InitialStateNotSet]));
+
+        // state 3 is only reachable from state 2, state 4 is not reachable at all
+        let mut machine = TuringMachine::new();
+        let [q1, q2, q3, q4] = [1, 2, 3, 4].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2, q3, q4]);
+        machine.set_initial_state(1).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Right, State::Halt),
+            TransitionRule::new(q2, Symbol::One, Symbol::One, Direction::Left, State::ProgramState(q3)),
+            TransitionRule::new(q4, Symbol::One, Symbol::One, Direction::Left, State::Halt),
+        ]).unwrap();
+
+        assert_eq!(machine.validate_completeness(), Err(vec![
+            TuringError::MissingRule { state: 2, symbol: Symbol::Zero },
+            TuringError::MissingRule { state: 3, symbol: Symbol::Zero },
+            TuringError::MissingRule { state: 3, symbol: Symbol::One },
+        ]));
+
+        // a default rule covers every symbol of its state and makes its target reachable,
+        // halting targets need no rules of their own
+        machine.define_default_rule(q3, Symbol::Zero, Direction::Stay, State::ProgramState(q4)).unwrap();
+        assert_eq!(machine.validate_completeness(), Err(vec![
+            TuringError::MissingRule { state: 2, symbol: Symbol::Zero },
+            TuringError::MissingRule { state: 4, symbol: Symbol::Zero },
+        ]));
+        machine.define_wildcard_rule(q2, None, Direction::Left, State::Accept).unwrap();
+        machine.define_wildcard_rule(q4, None, Direction::Left, State::Reject).unwrap();
+        assert_eq!(machine.validate_completeness(), Ok(()));
+    }
+
+    #[test]
+    fn accept_even_length_test() {
+        let mut machine = TuringMachine::new();
+        let [even, odd] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[even, odd]);
+        machine.define_transition_table(&[
+            TransitionRule::new(even, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(odd)),
+            TransitionRule::new(even, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Accept),
+            TransitionRule::new(odd, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(even)),
+            TransitionRule::new(odd, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Reject),
+        ]).unwrap();
+
+        for len in 0..6 {
+            let mut machine = machine.clone();
+            machine.set_initial_state(even.id).unwrap();
+            machine.write_to_tape(&Symbol::unary(len)).unwrap();
+
+            let state = machine.run().unwrap();
+            assert_eq!(state, if len % 2 == 0 { State::Accept } else { State::Reject });
+            assert!(state.is_halting());
+            assert_eq!(state.is_accepting(), len % 2 == 0);
+        }
+        assert!(!State::define(1).is_halting());
+        assert!(State::Halt.is_halting() && !State::Termination.is_accepting());
+    }
+
+    #[test]
+    fn wildcard_rule_test() {
+        // scans right over any symbol until it reaches two zeros in a row
+        let mut machine = TuringMachine::new();
+        let [scan, zero] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[scan, zero]);
+        machine.set_initial_state(scan.id).unwrap();
+        machine.define_wildcard_rule(scan, None, Direction::Right, State::ProgramState(scan)).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(scan, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(zero)),
+            TransitionRule::new(zero, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination),
+        ]).unwrap();
+        machine.define_wildcard_rule(zero, None, Direction::Right, State::ProgramState(scan)).unwrap();
+        let input = Symbol::vec_from_str("1101011").unwrap();
+        machine.write_to_tape(&input).unwrap();
+        let start = machine.head();
+
+        let rule = machine.get_transition_rule(&scan.id, &Symbol::One).unwrap();
+        assert_eq!((rule.from_symbol, rule.new_symbol), (Symbol::One, Symbol::One));
+        assert!(matches!(machine.get_transition_rule(&scan.id, &Symbol::Zero).unwrap().to_state, State::ProgramState(ProgramState { id: 2 })));
+
+        assert_eq!(machine.run(), Ok(State::Termination));
+        assert_eq!(machine.head(), start + input.len() + 1);
+        assert_eq!(machine.read_observed(), input);
+        assert_eq!(machine.validate_completeness(), Ok(()));
+
+        // a single wildcard rule handles both symbols
+        let mut eraser = TuringMachine::new();
+        eraser.define_states(&[scan]);
+        eraser.set_initial_state(scan.id).unwrap();
+        eraser.define_wildcard_rule(scan, Some(Symbol::Zero), Direction::Stay, State::Halt).unwrap();
+        for symbol in [Symbol::Zero, Symbol::One] {
+            let mut eraser = eraser.clone();
+            eraser.write_to_tape(&[symbol]).unwrap();
+            assert_eq!(eraser.run(), Ok(State::Halt));
+            assert_eq!(eraser.get_head_value(), Symbol::Zero);
+        }
+        assert_eq!(eraser.define_wildcard_rule(zero, None, Direction::Stay, State::Halt), Err(TuringError::StateNotDefined(2)));
+    }
+
+    #[test]
+    fn default_rule_test() {
+        // replaces every symbol but `2` with `1` while moving right, stops at the first `2`
+        let [q1] = [1].map(|id| ProgramState { id });
+        let mut explicit = TuringMachine::with_alphabet_size(4).unwrap();
+        explicit.define_states(&[q1]);
+        explicit.set_initial_state(q1.id).unwrap();
+        let mut with_default = explicit.clone();
+
+        explicit.define_transition_table(&[0, 1, 3].map(|symbol| {
+            TransitionRule::new(q1, Symbol::new(symbol), Symbol::One, Direction::Right, State::ProgramState(q1))
+        })).unwrap();
+        with_default.define_default_rule(q1, Symbol::One, Direction::Right, State::ProgramState(q1)).unwrap();
+        for machine in [&mut explicit, &mut with_default] {
+            machine.define_transition_table(&[
+                TransitionRule::new(q1, Symbol::new(2), Symbol::new(2), Direction::Stay, State::Termination),
+            ]).unwrap();
+            machine.write_to_tape(&Symbol::vec_from_numbers(&[3, 0, 1, 3, 2])).unwrap();
+        }
+
+        let (explicit_state, explicit_trace) = explicit.run_traced().unwrap();
+        let (default_state, default_trace) = with_default.run_traced().unwrap();
+        assert_eq!(explicit_state, State::Termination);
+        assert_eq!(default_state, explicit_state);
+        assert!(default_trace == explicit_trace);
+        assert_eq!(with_default.read_observed(), explicit.read_observed());
+
+        // wildcard rules take precedence over the default rule
+        let mut machine = TuringMachine::new();
+        machine.define_states(&[q1]);
+        machine.define_default_rule(q1, Symbol::Zero, Direction::Left, State::Halt).unwrap();
+        assert!(matches!(machine.get_transition_rule(&1, &Symbol::One).unwrap().head_move_dir, Direction::Left));
+        machine.define_wildcard_rule(q1, None, Direction::Right, State::Halt).unwrap();
+        assert!(matches!(machine.get_transition_rule(&1, &Symbol::One).unwrap().head_move_dir, Direction::Right));
+        assert_eq!(machine.define_default_rule(q1, Symbol::new(2), Direction::Stay, State::Halt), Err(TuringError::SymbolNotInAlphabet(Symbol::new(2))));
+    }
+
+    #[test]
+    fn define_transition_table_from_tuples_test() {
+        let mut machine = TuringMachine::new();
+        machine.define_states(&[1, 2, 3, 4].map(|id| ProgramState { id }));
+        machine.set_initial_state(1).unwrap();
+        machine.define_transition_table(&[
+            (1, Symbol::Zero, Symbol::Zero, Direction::Stay, StateTarget::Term),
+            (1, Symbol::One, Symbol::Zero, Direction::Right, StateTarget::To(2)),
+            (2, Symbol::Zero, Symbol::One, Direction::Left, StateTarget::To(3)),
+            (2, Symbol::One, Symbol::One, Direction::Right, StateTarget::To(2)),
+            (3, Symbol::Zero, Symbol::Zero, Direction::Right, StateTarget::To(4)),
+            (3, Symbol::One, Symbol::One, Direction::Left, StateTarget::To(3)),
+            (4, Symbol::Zero, Symbol::Zero, Direction::Stay, StateTarget::Halt),
+            (4, Symbol::One, Symbol::Zero, Direction::Right, StateTarget::Term),
+        ]).unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1])).unwrap();
+
+        assert_eq!(machine.run(), Ok(State::Termination));
+        assert_eq!(machine.read_unary_blocks(), vec![6]);
+        assert_eq!(
+            machine.define_transition_table(&[(5, Symbol::Zero, Symbol::Zero, Direction::Stay, StateTarget::Halt)]),
+            Err(TuringError::StateNotDefined(5)),
+        );
+    }
+
+    #[test]
+    fn with_alphabet_size_test() {
+        assert_eq!(TuringMachine::with_alphabet_size(0).err(), Some(TuringError::AlphabetSize { max: 36 }));
+        assert_eq!(TuringMachine::with_alphabet_size(37).err(), Some(TuringError::AlphabetSize { max: 36 }));
+        assert_eq!(TuringMachine::with_alphabet_size(2).unwrap().alphabet(), TuringMachine::new().alphabet());
+        assert_eq!(TuringMachine::with_alphabet_size(12).unwrap().alphabet().last(), Some(&'b'));
+    }
+
+    #[test]
+    fn with_alphabet_size_runs_ternary_counter_test() {
+        // increments a base-3 number written most significant digit first, with the head on its last digit
+        let mut machine = TuringMachine::with_alphabet_size(3).unwrap();
+        let [blank, one, two] = [0, 1, 2].map(Symbol::new);
+        let [carry, done] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[carry, done]);
+        machine.set_initial_state(carry.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(carry, two, blank, Direction::Left, State::ProgramState(carry)),
+            TransitionRule::new(carry, one, two, Direction::Stay, State::Termination),
+            TransitionRule::new(carry, blank, one, Direction::Stay, State::Termination),
+        ]).unwrap();
+        let input = Symbol::vec_from_numbers(&[1, 2, 2]);
+        machine.write_to_tape(&input).unwrap();
+        let start = machine.head();
+        for _ in 1..input.len() {
+            machine.move_head(Direction::Right);
+        }
+
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+        assert_eq!(machine.head(), start);
+        assert!(read_from_head(&mut machine, input.len()) == Symbol::vec_from_numbers(&[2, 0, 0]));
+    }
+
+    #[test]
+    fn with_blank_rule_fires_at_tape_edge_test() {
+        // appends a one after the input, zeros inside the input must not be mistaken for the blank
+        let mut machine = TuringMachine::with_blank();
+        let blank = machine.blank();
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(q1)),
+            TransitionRule::new(q1, blank, Symbol::One, Direction::Stay, State::Termination),
+        ]).unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[0, 1, 0, 0])).unwrap();
+        let start = machine.head();
+
+        assert!(machine.get_transition_rule(&q1.id, &blank).is_some());
+        assert!(matches!(machine.run(), Ok(State::Termination)));
+        assert_eq!(machine.head(), start + 4);
+        assert!(machine.format_tape(true).starts_with("___"));
+        assert!(machine.format_tape(true).contains("0100\x1b[32m\x1b[4m1\x1b[0m___"));
+        assert!(machine.format_tape(false).contains("0100[1]___"));
+        assert!(!machine.format_tape(false).contains('\x1b'));
+    }
+
+    #[test]
+    fn display_and_debug_test() {
+        let rule = TransitionRule::new(ProgramState { id: 1 }, Symbol::One, Symbol::Zero, Direction::Right, State::define(2));
+
+        assert_eq!(format!("{} {} {}", Symbol::Zero, Symbol::One, Symbol::new(2)), "0 1 2");
+        assert_eq!(format!("{} {} {}", Direction::Left, Direction::Right, Direction::Stay), "L R S");
+        assert_eq!(
+            format!("{:?}", rule),
+            "TransitionRule { from_state: ProgramState { id: 1 }, from_symbol: Symbol(1), to_state: ProgramState(ProgramState { id: 2 }), new_symbol: Symbol(0), head_move_dir: Right }",
+        );
+        assert_eq!(format!("{:?} {:?}", State::Halt, State::Termination), "Halt Termination");
+        assert_eq!(format!("{} {} {}", rule.to_state, State::Halt, State::Termination), "q2 HALT TERM");
+        assert_eq!(rule.from_state.to_string(), "q1");
+    }
+
+    #[test]
+    fn duplicate_rule_error_message_test() {
+        let mut machine = TuringMachine::new();
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+
+        let result = machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::One, Symbol::Zero, Direction::Stay, State::Halt),
+            TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Stay, State::Halt),
+        ]);
+
+        assert_eq!(
+            result,
+            Err(TuringError::DuplicateRule { state: 1, label: None, symbol: Symbol::One }),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ERROR: State with id `1` is already bound to a transition rule for symbol `1` as a `from_state`",
+        );
+
+        machine.define_named_states(&[(1, "scan_right")]);
+        let named_result = machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::One, Symbol::Zero, Direction::Stay, State::Halt),
+            TransitionRule::new(q1, Symbol::One, Symbol::One, Direction::Stay, State::Halt),
+        ]);
+        assert_eq!(
+            named_result.unwrap_err().to_string(),
+            "ERROR: State `scan_right` (id `1`) is already bound to a transition rule for symbol `1` as a `from_state`",
+        );
+    }
+
+    #[test]
+    fn symbol_from_str_test() {
+        assert_eq!(Symbol::vec_from_str("1101"), Ok(Symbol::vec_from_numbers(&[1, 1, 0, 1])));
+        assert_eq!(Symbol::vec_from_str(""), Ok(vec![]));
+        assert_eq!(Symbol::vec_from_str("10 1"), Err(TuringError::InvalidSymbol(' ')));
+        assert!(Symbol::vec_from_str("1-0").is_err());
+
+        assert_eq!(Symbol::from_char('1'), Ok(Symbol::One));
+        assert_eq!(Symbol::One.to_char(), Some('1'));
+        assert_eq!(Symbol::new(11).to_char(), Some('b'));
+        assert_eq!(Symbol::new(36).to_char(), None);
+    }
+
+    #[test]
+    fn display_marks_head_test() {
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 0, 1, 0, 1])).unwrap();
+        for _ in 0..3 {
+            machine.move_head(Direction::Right);
+        }
+        assert_eq!(machine.to_string(), "110[1]01");
+        machine.move_head_by(3);
+        assert_eq!(format!("{}", machine), "110101[0]");
+        machine.move_head_by(-8);
+        assert_eq!(format!("{}", machine), "[0]0110101");
+
+        assert_eq!(TuringMachine::new().to_string(), "[0]");
+        let mut machine = TuringMachine::with_blank();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[0, 1])).unwrap();
+        assert_eq!(machine.to_string(), "[0]1");
+    }
+
+    #[test]
+    fn observed_to_string_uses_full_cell_width_test() {
+        let mut machine = TuringMachine::new();
+        machine.tape = [0, 1, 0, usize::MAX, 0].map(BitCell::new).to_vec();
+
+        let observed_area = machine.observed_to_string(None);
+
+        assert_eq!(observed_area.len(), 3 * USIZE_BIT_SIZE);
+        assert_eq!(observed_area, format!("{}1{}{}", "0".repeat(USIZE_BIT_SIZE - 1), "0".repeat(USIZE_BIT_SIZE), "1".repeat(USIZE_BIT_SIZE)));
+    }
+
+    #[test]
+    fn fmt_tape_writes_to_any_sink_test() {
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 0, 1])).unwrap();
+        machine.move_head(Direction::Right);
+
+        let mut output = Vec::new();
+        machine.fmt_tape(&mut output, false).unwrap();
+        let plain = String::from_utf8(output).unwrap();
+        assert!(plain.ends_with("\n"));
+        assert!(plain.contains("1[0]1"));
+        assert_eq!(plain.len(), machine.tape_len() + 3);
+
+        let mut output = Vec::new();
+        machine.fmt_tape(&mut output, true).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("1\x1b[32m\x1b[4m0\x1b[0m1"));
+
+        let mut output = Vec::new();
+        machine.fmt_tape_observed_area(&mut output, None).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), machine.observed_to_string(None) + "\n");
+    }
+
+    #[test]
+    fn tape_to_string_test() {
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 0, 1, 1])).unwrap();
+        let start = machine.head();
+
+        let tape = machine.tape_to_string();
+        assert_eq!(tape.len(), machine.tape_len());
+        assert_eq!(&tape[start..start + 4], "1011");
+        assert_eq!(tape.matches('1').count(), 3);
+
+        let mut machine = TuringMachine::with_blank();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[0, 1])).unwrap();
+        assert!(machine.tape_to_string().contains("_01_"));
+    }
+
+    #[test]
+    fn history_test() {
+        let mut machine = example_machine();
+        let mut expected = Vec::new();
+        let mut stepped = machine.clone();
+        while let Some(State::ProgramState(ProgramState { id })) = stepped.current_state() {
+            let window = stepped.to_string();
+            let head = window.find('[').unwrap();
+            let symbols = Symbol::vec_from_str(&window.replace(['[', ']'], "")).unwrap();
+            expected.push((id, head, symbols, stepped.visited_range));
+            stepped.step().unwrap();
+        }
+
+        assert_eq!(machine.history(), &[]);
+        machine.enable_history(true);
+        machine.run().unwrap();
+        assert_eq!(machine.history(), expected);
+        assert_eq!(machine.history().len(), 10);
+        assert_eq!(machine.history()[0], (1, 0, Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1]), (0, 0)));
+        assert_eq!(machine.history()[1].3, (0, 1));
+
+        machine.reset_with_input(&Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1])).unwrap();
+        machine.set_history_limit(3);
+        machine.run().unwrap();
+        assert_eq!(machine.history(), &expected[..3]);
+
+        machine.enable_history(false);
+        assert_eq!(machine.history(), &[]);
+    }
+
+    #[test]
+    fn step_back_test() {
+        // restored from the history, partly replayed once the history is full, and replayed
+        for (history, limit) in [(true, HISTORY_MAX_LEN), (true, 2), (false, HISTORY_MAX_LEN)] {
+            let mut machine = example_machine();
+            machine.enable_history(history);
+            machine.set_history_limit(limit);
+            assert_eq!(machine.step_back(), Err(TuringError::NoStepToUndo));
+
+            let mut configurations = Vec::new();
+            for _ in 0..3 {
+                let configuration = (machine.current_state(), machine.position(), machine.to_string(), machine.steps());
+                configurations.push((configuration, machine.space_used()));
+                machine.step().unwrap();
+            }
+            assert_eq!(machine.space_used(), 4);
+            machine.step_back().unwrap();
+            assert_eq!(machine.space_used(), configurations[2].1);
+            machine.step_back().unwrap();
+            let configuration = (machine.current_state(), machine.position(), machine.to_string(), machine.steps());
+            assert_eq!((configuration, machine.space_used()), configurations[1]);
+            assert_eq!(machine.space_used(), 2);
+
+            // stepping again after going back continues the same run
+            let mut reference = example_machine();
+            machine.run().unwrap();
+            reference.run().unwrap();
+            assert_eq!(machine.to_string(), reference.to_string());
+            assert_eq!(machine.current_state(), reference.current_state());
+
+            machine.step_back().unwrap();
+            assert_eq!(machine.steps(), reference.steps() - 1);
+            assert!(matches!(machine.current_state(), Some(State::ProgramState(_))));
+        }
+    }
+
+    #[test]
+    fn step_back_replay_test() {
+        // the tape changed in between steps, so the replay halts before reaching the step to undo
+        let mut machine = TuringMachine::from_program("q1 1 -> 1 R q1").unwrap();
+        machine.write_to_tape(&[Symbol::One]).unwrap();
+        machine.step().unwrap();
+        machine.write_to_tape(&[Symbol::One, Symbol::One]).unwrap();
+        machine.step().unwrap();
+        machine.step().unwrap();
+        let stopped = (machine.current_state(), machine.position(), machine.to_string(), machine.steps());
+        assert_eq!(machine.step_back(), Err(TuringError::ReplayDiverged(1)));
+        assert_eq!((machine.current_state(), machine.position(), machine.to_string(), machine.steps()), stopped);
+
+        // a history that is exactly full drops the configuration of the undone step
+        let mut machine = example_machine();
+        machine.enable_history(true);
+        machine.set_history_limit(3);
+        let mut configurations = Vec::new();
+        for _ in 0..3 {
+            configurations.push((machine.current_state(), machine.position(), machine.to_string(), machine.steps()));
+            machine.step().unwrap();
+        }
+        machine.step_back().unwrap();
+        assert_eq!(machine.history().len(), 2);
+        machine.step_back().unwrap();
+        assert_eq!((machine.current_state(), machine.position(), machine.to_string(), machine.steps()), configurations[1]);
+    }
+
+    #[test]
+    fn step_back_undoes_halting_without_rule_test() {
+        let mut machine = TuringMachine::from_program("q1 1 -> 0 R q1").unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1])).unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.outcome(), Some(Outcome::HaltedNoRule));
+
+        machine.step_back().unwrap();
+        assert_eq!(machine.current_state(), Some(State::define(1)));
+        assert_eq!(machine.steps(), 2);
+        assert_eq!((machine.position(), machine.to_string()), (2, "[0]".to_string()));
+        machine.step_back().unwrap();
+        assert_eq!((machine.position(), machine.to_string()), (1, "[1]".to_string()));
+        assert_eq!(machine.steps(), 1);
+    }
+
+    #[test]
+    fn fmt_tape_rle_test() {
+        let mut machine = TuringMachine::new();
+        let mut input = vec![Symbol::One; 3];
+        input.extend([Symbol::Zero; 12]);
+        input.extend([Symbol::One; 5]);
+        machine.write_to_tape(&input).unwrap();
+        assert_eq!(machine.fmt_tape_rle(), "[1^3] 0^12 1^5");
+
+        machine.move_head_by(10);
+        assert_eq!(machine.fmt_tape_rle(), "1^3 [0^12] 1^5");
+        machine.move_head_by(-15);
+        assert_eq!(machine.fmt_tape_rle(), "[0^5] 1^3 0^12 1^5");
+
+        // runs are made of symbols, not of the bits of the cells
+        let mut machine = TuringMachine::with_alphabet(&['_', 'a', 'b', 'c'], '_').unwrap();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 2, 3, 3, 3])).unwrap();
+        assert_eq!(machine.fmt_tape_rle(), "[a^2] b^1 c^3");
+        assert_eq!(TuringMachine::new().fmt_tape_rle(), "[0^1]");
+    }
+
+    #[test]
+    fn fmt_tape_with_head_at_tape_edges_test() {
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1])).unwrap();
+        let tape_len = machine.tape_len();
+
+        machine.move_head_by(-(machine.head() as isize));
+        assert_eq!(machine.head(), 0);
+        let mut output = Vec::new();
+        machine.fmt_tape(&mut output, false).unwrap();
+        assert!(String::from_utf8(output).unwrap().starts_with("[0]"));
+
+        machine.move_head_by(tape_len as isize - 1);
+        assert_eq!(machine.head(), tape_len - 1);
+        assert_eq!(machine.tape_len(), tape_len);
+        let mut output = Vec::new();
+        machine.fmt_tape(&mut output, false).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with("[0]\n"));
+        let mut output = Vec::new();
+        machine.fmt_tape(&mut output, true).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with("\x1b[32m\x1b[4m0\x1b[0m\n"));
+    }
+
+    #[test]
+    fn observed_to_string_includes_last_non_zero_cell_test() {
+        let cell = |bits: usize| format!("{:0width$b}", bits, width = USIZE_BIT_SIZE);
+        let mut machine = TuringMachine::new();
+
+        machine.tape = [5, 0, 0, 0].map(BitCell::new).to_vec();
+        assert_eq!(machine.observed_to_string(None), cell(5));
+        assert_eq!(machine.observed_to_string(Some(2)), cell(5) + &cell(0) + &cell(0));
+
+        machine.tape = [0, 0, 0, 6].map(BitCell::new).to_vec();
+        assert_eq!(machine.observed_to_string(None), cell(6));
+        assert_eq!(machine.observed_to_string(Some(1)), cell(0) + &cell(6));
+
+        machine.tape = [0, 0, 7, 0, 0].map(BitCell::new).to_vec();
+        assert_eq!(machine.observed_to_string(None), cell(7));
+        assert_eq!(machine.observed_to_string(Some(1)), cell(0) + &cell(7) + &cell(0));
+        assert_eq!(machine.observed_to_string(Some(usize::MAX)), cell(0).repeat(2) + &cell(7) + &cell(0).repeat(2));
+
+        machine.tape = vec![BitCell::ZERO; 2];
+        assert_eq!(machine.observed_to_string(Some(1)), cell(0).repeat(2));
+
+        // a single-cell tape used to print nothing at all
+        machine.tape = vec![BitCell::new(usize::MAX)];
+        assert_eq!(machine.observed_to_string(None), cell(usize::MAX));
+        assert_eq!(machine.observed_to_string(Some(3)), cell(usize::MAX));
+    }
+
+    #[test]
+    fn read_tape_test() {
+        let mut machine = example_machine();
+        let start = machine.head();
+        assert_eq!(machine.read_tape(start, 8), Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1]));
+        assert_eq!(machine.read_observed(), Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1]));
+
+        machine.run().unwrap();
+
+        assert_eq!(machine.read_tape(start, 8), Symbol::vec_from_numbers(&[0, 0, 1, 1, 1, 1, 1, 1]));
+        assert_eq!(machine.read_observed(), vec![Symbol::One; 6]);
+        assert_eq!(machine.read_tape(machine.tape_len() - 1, 3), vec![Symbol::Zero; 3]);
+        assert_eq!(TuringMachine::new().read_observed(), vec![]);
+    }
+
+    #[test]
+    fn read_write_uint_test() {
+        let mut machine = TuringMachine::new();
+        let start = machine.head() + 3;
+
+        machine.write_uint(13, start, 4).unwrap();
+
+        assert_eq!(machine.read_tape(start, 4), Symbol::vec_from_numbers(&[1, 1, 0, 1]));
+        assert_eq!(machine.read_uint(start, 4), Ok(13));
+        assert_eq!(machine.read_uint(start - 2, 8), Ok(13 << 2));
+
+        machine.write_uint(u64::MAX, start, 64).unwrap();
+        assert_eq!(machine.read_uint(start, 64), Ok(u64::MAX));
+        assert_eq!(machine.write_uint(16, start, 4), Err(TuringError::ValueTooLarge { value: 16, len: 4 }));
+        assert_eq!(machine.write_uint(0, start, 65), Err(TuringError::UintTooWide(65)));
+        assert_eq!(machine.read_uint(start, 65), Err(TuringError::UintTooWide(65)));
+        assert_eq!(TuringMachine::with_blank().read_uint(0, 1), Err(TuringError::NotBinary(Symbol::new(2))));
+    }
+
+    #[test]
+    fn tape_symbols_test() {
+        let machine = example_machine();
+        let start = machine.head();
+
+        let symbols = machine.tape_symbols().collect::<Vec<_>>();
+
+        assert_eq!(symbols.len(), machine.tape_len());
+        assert_eq!(symbols[start..start + 8], Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1]));
+        assert_eq!(symbols.iter().filter(|&&symbol| symbol == Symbol::One).count(), 7);
+        assert_eq!(machine.symbol_at(start + 4), Symbol::Zero);
+        assert_eq!(machine.symbol_at(start + 5), Symbol::One);
+        assert_eq!(machine.symbol_at(machine.tape_len()), Symbol::Zero);
+
+        // the input survives the tape growing on both sides
+        let mut machine = example_machine();
+        let input = machine.read_observed();
+        machine.move_head_by(-(machine.head() as isize) - 1);
+        machine.move_head_by(machine.tape_len() as isize + 1);
+        let symbols = machine.tape_symbols().collect::<Vec<_>>();
+        assert_eq!(symbols.len(), machine.tape_len());
+        assert_eq!(machine.read_observed(), input);
+        assert_eq!(symbols.iter().filter(|&&symbol| symbol == Symbol::One).count(), 7);
+    }
+
+    #[test]
+    fn read_write_tracks_test() {
+        let mut machine = TuringMachine::with_alphabet_size(3).unwrap();
+        let position = machine.tape_len() / 3;
+
+        machine.write_tracks(position, &Symbol::vec_from_numbers(&[1, 2, 1])).unwrap();
+        machine.write_tracks(position + 1, &Symbol::vec_from_numbers(&[0, 0, 2])).unwrap();
+
+        assert_eq!(machine.read_tracks(3, position), Symbol::vec_from_numbers(&[1, 2, 1]));
+        assert_eq!(machine.read_tracks(3, position + 1), Symbol::vec_from_numbers(&[0, 0, 2]));
+        assert_eq!(machine.read_tape(position * 3, 6), Symbol::vec_from_numbers(&[1, 2, 1, 0, 0, 2]));
+        assert_eq!(machine.read_tracks(3, position + 2), vec![Symbol::Zero; 3]);
+
+        // writing past the end of the tape grows it
+        let far = machine.tape_len();
+        machine.write_tracks(far, &[Symbol::One, Symbol::One]).unwrap();
+        assert_eq!(machine.read_tracks(2, far), vec![Symbol::One; 2]);
+    }
+
+    #[test]
+    fn write_at_test() {
+        // two operands separated by a blank, written without moving the head
+        let mut machine = TuringMachine::new();
+        let head = machine.head();
+        machine.write_at(head, &Symbol::unary(3)).unwrap();
+        machine.write_at(head + 4, &Symbol::unary(2)).unwrap();
+
+        assert_eq!(machine.head(), head);
+        assert_eq!(machine.read_unary_blocks(), vec![3, 2]);
+
+        let end = machine.tape_len();
+        machine.write_at(end + 10, &[Symbol::One]).unwrap();
+        assert!(machine.tape_len() > end + 10);
+        assert_eq!(machine.read_tape(end + 9, 3), Symbol::vec_from_numbers(&[0, 1, 0]));
+    }
+
+    #[test]
+    fn set_head_test() {
+        // the input starts left of the head, which is then put on its first cell
+        let mut machine = TuringMachine::from_program("q1 1 -> 0 R q1\nq1 0 -> 0 S halt").unwrap();
+        let head = machine.head();
+        machine.write_at(head - 10, &Symbol::unary(3)).unwrap();
+        assert_eq!(machine.head(), head);
+        assert_eq!(machine.get_head_value(), Symbol::Zero);
+
+        machine.set_head(head - 10);
+        assert_eq!(machine.position(), -10);
+        assert_eq!((machine.visited_range(), machine.space_used()), ((head - 10, head - 10), 1));
+        assert_eq!(machine.run(), Ok(State::Halt));
+        assert_eq!(machine.position(), -7);
+        assert_eq!(machine.space_used(), 4);
+        assert_eq!(machine.read_observed(), vec![]);
+
+        let end = machine.tape_len();
+        machine.set_head(end + 3);
+        assert_eq!(machine.head(), end + 3);
+        assert!(machine.tape_len() > end + 3);
+        assert_eq!(machine.space_used(), 1);
+    }
+
+    #[test]
+    fn read_tape_range_test() {
+        let machine = example_machine();
+        let start = machine.head();
+        let tape_len = machine.tape_len();
+
+        assert_eq!(machine.read_tape_range(start + 3, start + 6), Symbol::vec_from_numbers(&[1, 0, 1]));
+        assert_eq!(machine.read_tape_range(start, start), vec![]);
+        assert_eq!(machine.read_tape_range(start + 5, start + 2), vec![]);
+        assert_eq!(machine.read_tape_range(tape_len - 2, tape_len + 10).len(), 2);
+        assert_eq!(machine.read_tape_range(tape_len + 1, tape_len + 10), vec![]);
+    }
+
+    #[test]
+    fn read_unary_blocks_test() {
+        // the example adds 3 and 2 written as blocks of n + 1 ones
+        let mut machine = example_machine();
+        assert_eq!(machine.read_unary_blocks(), vec![4, 3]);
+
+        machine.run().unwrap();
+
+        assert_eq!(machine.read_unary_blocks(), vec![6]);
+        assert!(TuringMachine::new().read_unary_blocks().is_empty());
+
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&[vec![Symbol::Zero; 3], Symbol::unary(2), vec![Symbol::Zero; 2], Symbol::unary(1)].concat()).unwrap();
+        assert_eq!(machine.read_unary_blocks(), vec![2, 1]);
+        assert_eq!(Symbol::unary(0), vec![]);
+    }
+
+    #[test]
+    fn count_ones_test() {
+        let mut machine = TuringMachine::new();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 1, 0, 1])).unwrap();
+        machine.write_at(machine.head() - 10, &Symbol::unary(5)).unwrap();
+        assert_eq!(machine.count_ones(), 8);
+
+        // grows the tape on both sides, which keeps the count
+        machine.head = 0;
+        machine.move_head(Direction::Left);
+        machine.set_head_value(Symbol::One).unwrap();
+        machine.write_at(machine.tape_len() + 3, &[Symbol::One]).unwrap();
+        assert_eq!(machine.count_ones(), 10);
+        assert_eq!(machine.count_ones(), machine.tape_symbols().filter(|&symbol| symbol == Symbol::One).count());
+
+        // the input was written around the boundary of the first two cells of the initial tape
+        let shift = machine.origin - USIZE_BIT_SIZE;
+        assert_eq!(machine.count_ones_in(shift + USIZE_BIT_SIZE - 7, 9), 4);
+        assert_eq!(machine.count_ones_in(shift + USIZE_BIT_SIZE - 1, 2), 1);
+        assert_eq!(machine.count_ones_in(shift, 0), 0);
+        assert_eq!(machine.count_ones_in(machine.tape_len() - 1, 10), 0);
+
+        let mut machine = TuringMachine::with_blank();
+        machine.write_to_tape(&Symbol::vec_from_numbers(&[1, 0, 1, 1])).unwrap();
+        assert_eq!(machine.count_ones(), 3);
+        assert_eq!(machine.count_ones_in(machine.head() + 1, 2), 1);
+    }
+
+    #[test]
+    fn write_binary_test() {
+        let mut machine = TuringMachine::new();
+        let head = machine.head();
+        machine.write_binary(0b1101, 6).unwrap();
+        assert_eq!(machine.read_tape(head, 6), Symbol::vec_from_numbers(&[0, 0, 1, 1, 0, 1]));
+        assert_eq!(machine.read_binary(head, 6), Ok(13));
+        assert_eq!(machine.read_binary(head + 2, 4), Ok(13));
+        assert_eq!(machine.head(), head);
+
+        for value in [0, 1, 0x8000_0000_0000_0001, u64::MAX] {
+            machine.write_binary(value, 64).unwrap();
+            assert_eq!(machine.read_binary(head, 64), Ok(value));
+        }
+        assert_eq!(machine.write_binary(8, 3), Err(TuringError::ValueTooLarge { value: 8, len: 3 }));
+        assert_eq!(machine.write_binary(1, 65), Err(TuringError::UintTooWide(65)));
+        assert_eq!(machine.read_binary(head, 65), Err(TuringError::UintTooWide(65)));
+        assert_eq!(machine.read_binary(head, 64), Ok(u64::MAX));
+        assert_eq!(TuringMachine::with_blank().read_binary(0, 1), Err(TuringError::NotBinary(Symbol::new(2))));
+    }
+
+    #[test]
+    fn write_unary_test() {
+        let mut machine = TuringMachine::new();
+        let head = machine.head();
+        machine.write_unary(5).unwrap();
+        assert_eq!(machine.count_ones_run(head), 5);
+        assert_eq!(machine.head(), head);
+
+        // the example adds 3 and 2 written as blocks of n + 1 ones
+        let mut adder = example_machine();
+        adder.reset();
+        adder.write_unary_pair(4, 3).unwrap();
+        assert_eq!(adder.read_observed(), Symbol::vec_from_numbers(&[1, 1, 1, 1, 0, 1, 1, 1]));
+        adder.run().unwrap();
+        assert_eq!(adder.read_unary_blocks(), vec![6]);
+        assert_eq!(adder.count_ones_run(adder.head()), 6);
+    }
+
+    #[test]
+    fn count_ones_run_test() {
+        let mut machine = example_machine();
+        let start = machine.head();
+        assert_eq!(machine.count_ones_run(start), 4);
+        assert_eq!(machine.count_ones_run(start + 4), 0);
+        assert_eq!(machine.count_ones_run(start + 5), 3);
+
+        machine.run().unwrap();
+        assert_eq!(machine.count_ones_run(machine.head()), 6);
+
+        let tape_len = machine.tape_len();
+        machine.write_uint(0b111, tape_len - 3, 3).unwrap();
+        assert_eq!(machine.count_ones_run(tape_len - 3), 3);
+        assert_eq!(machine.count_ones_run(tape_len + 5), 0);
+    }
+
+    #[test]
+    fn run_detect_loop_test() {
+        // q1 and q2 keep flipping the same cell back and forth
+        let mut machine = TuringMachine::new();
+        let [q1, q2] = [1, 2].map(|id| ProgramState { id });
+        machine.define_states(&[q1, q2]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::One, Direction::Stay, State::ProgramState(q2)),
+            TransitionRule::new(q2, Symbol::One, Symbol::Zero, Direction::Stay, State::ProgramState(q1)),
+        ]).unwrap();
+
+        assert_eq!(machine.clone().run_detect_loop(1), Err(TuringError::StepLimitExceeded(1)));
+        assert_eq!(
+            machine.run_detect_loop(100),
+            Err(TuringError::LoopDetected(2)),
+        );
+        assert_eq!(example_machine().run_detect_loop(100), Ok(State::Termination));
+    }
+}
@@ -1,6 +1,117 @@
-use turing_machine::{ Direction, ProgramState, State, Symbol, TransitionRule, TuringMachine };
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use turing_machine::{ Direction, ProgramState, State, Symbol, TransitionRule, TuringError, TuringMachine };
 
-fn main() {
+const USAGE: &str = "usage: turing-machine [--quiet] [--max-steps <n>] [<machine.tm|machine.json> <input>]";
+
+// exit codes of the final states, errors exit with `ExitCode::FAILURE`
+const EXIT_HALT: u8 = 2;
+const EXIT_REJECT: u8 = 3;
+
+struct Options {
+    machine_path: String,
+    input: String,    // a file holding the input, or the input itself
+    max_steps: u64,
+    quiet: bool,      // suppresses the state and tape of every step
+}
+
+// `None` runs the built-in example
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut positional = Vec::new();
+    let (mut max_steps, mut quiet) = (u64::MAX, false);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quiet" => quiet = true,
+            "--max-steps" => {
+                let value = args.next().ok_or("`--max-steps` needs a value")?;
+                max_steps = value.parse().map_err(|_| format!("invalid step limit `{}`", value))?;
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option `{}`", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    match <[String; 2]>::try_from(positional) {
+        Ok([machine_path, input]) => Ok(Some(Options { machine_path, input, max_steps, quiet })),
+        Err(positional) if positional.is_empty() => Ok(None),
+        Err(_) => Err("expected a machine and an input".to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match parse_args(&args) {
+        Ok(None) => run_example(),
+        Ok(Some(options)) => run_file(&options),
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+        Ok(State::Termination | State::Accept) => ExitCode::SUCCESS,
+        Ok(State::Halt) => ExitCode::from(EXIT_HALT),
+        Ok(State::Reject) => ExitCode::from(EXIT_REJECT),
+        Ok(State::ProgramState(_)) => ExitCode::FAILURE,
+    }
+}
+
+fn read(path: &str) -> Result<String, TuringError> {
+    fs::read_to_string(path).map_err(|err| TuringError::Io(format!("failed to read `{}`: {}", path, err)))
+}
+
+// JSON definitions for `.json` files and text programs otherwise
+fn load_machine(path: &str) -> Result<TuringMachine, TuringError> {
+    if path.ends_with(".json") {
+        #[cfg(feature = "json")]
+        return TuringMachine::from_json(Path::new(path));
+        #[cfg(not(feature = "json"))]
+        return Err(TuringError::Io(format!("loading `{}` needs the `json` feature", path)));
+    }
+    TuringMachine::from_program(&read(path)?)
+}
+
+// runs the machine on the input, printing every step unless `quiet`, then the outcome and the final tape
+fn run_file(options: &Options) -> Result<State, TuringError> {
+    let mut machine = load_machine(&options.machine_path)?;
+    let input = if Path::new(&options.input).is_file() { read(&options.input)? } else { options.input.clone() };
+    let input = input
+        .trim()
+        .chars()
+        .map(|c| machine.symbol(c))
+        .collect::<Result<Vec<_>, _>>()?;
+    machine.write_to_tape(&input)?;
+
+    let state = if options.quiet {
+        machine.run_with_limit(options.max_steps)?
+    } else {
+        let mut steps = 0;
+        let state = machine.run_with_observer(|machine, rule| {
+            if steps == options.max_steps {
+                return false;
+            }
+            steps += 1;
+            println!("{}: {}", machine.format_state(rule.from_state.id), machine);
+            true
+        })?;
+        // the observer only stops the run at the step limit
+        if !state.is_halting() {
+            return Err(TuringError::StepLimitExceeded(options.max_steps));
+        }
+        state
+    };
+    println!("{:?}", machine.outcome().unwrap());
+    println!("{}", machine);
+    Ok(state)
+}
+
+fn run_example() -> Result<State, TuringError> {
     let mut machine = TuringMachine::new();
 
     let q1 = ProgramState{id: 1};
@@ -8,11 +119,11 @@ fn main() {
     let q3 = ProgramState{id: 3};
     let q4 = ProgramState{id: 4};
 
-    machine.define_states(&vec![ q1,q2,q3,q4 ]);
-    
+    machine.define_states(&[ q1,q2,q3,q4 ]);
+
     machine.set_initial_state(q1.id).expect("Initial state is not set");
-    
-    machine.define_transition_table(&vec![
+
+    machine.define_transition_table(&[
         TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination),
         TransitionRule::new(q1, Symbol::One, Symbol::Zero, Direction::Right, State::ProgramState(q2)),
 
@@ -31,23 +142,24 @@ fn main() {
         Symbol::Zero,
         Symbol::One, Symbol::One, Symbol::One, // 2
     ];
-    
-    machine.write_to_tape(&input);
-    
+
+    machine.write_to_tape(&input)?;
+
     println!("Initial tape:");
     machine.print_tape();
     println!("--------------------------------");
-    
-    match machine.run() {
-        Err(err) => println!("Error: {}", err),
-        Ok(finish_state) => match finish_state {
-            State::Halt => println!("Machine halted"),
-            State::Termination => {
-                println!("Machine terminated");
-                println!("Final tape:");
-                machine.print_tape();
-            },
-            State::ProgramState(ProgramState { id }) => println!("Machine stopped with invalid state with id `{}`", id),
-        }
+
+    let finish_state = machine.run_with_output(&mut std::io::stdout())?;
+    match finish_state {
+        State::Halt => println!("Machine halted"),
+        State::Termination => {
+            println!("Machine terminated");
+            println!("Final tape:");
+            machine.print_tape();
+        },
+        State::Accept => println!("Machine accepted"),
+        State::Reject => println!("Machine rejected"),
+        State::ProgramState(ProgramState { id }) => println!("Machine stopped with invalid state with id `{}`", id),
     }
+    Ok(finish_state)
 }
@@ -0,0 +1,282 @@
+use crate::{Direction, ProgramState, ProgramStateId, State, Symbol, TuringError, TuringMachine};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// A transition of a k-tape machine, reading one symbol from every tape and writing and moving every head at once.
+#[derive(Clone, Debug)]
+pub struct MultiTapeRule {
+    pub from_state: ProgramState,
+    pub from_symbols: Vec<Symbol>,
+    pub to_state: State,
+    pub new_symbols: Vec<Symbol>,
+    pub head_move_dirs: Vec<Direction>,
+}
+
+impl MultiTapeRule {
+    pub fn new(
+        from_state: ProgramState,
+        from_symbols: &[Symbol],
+        new_symbols: &[Symbol],
+        head_move_dirs: &[Direction],
+        to_state: State,
+    ) -> MultiTapeRule {
+        MultiTapeRule {
+            from_state,
+            from_symbols: from_symbols.to_vec(),
+            new_symbols: new_symbols.to_vec(),
+            head_move_dirs: head_move_dirs.to_vec(),
+            to_state,
+        }
+    }
+}
+
+/// A binary machine with several tapes, each with its own head.
+/// Every tape is the bit-vector tape of a plain `TuringMachine`, only its tape and head are used.
+pub struct MultiTapeTuringMachine {
+    tapes: Vec<TuringMachine>,
+    initial_state: Option<ProgramStateId>,
+    current_state: Option<State>,
+    states: HashMap<ProgramStateId, ProgramState>,
+    transition_table: HashMap<ProgramStateId, HashMap<Vec<Symbol>, MultiTapeRule>>,
+}
+
+impl MultiTapeTuringMachine {
+    pub fn new(tape_count: usize) -> MultiTapeTuringMachine {
+        MultiTapeTuringMachine {
+            tapes: (0..tape_count).map(|_| TuringMachine::new()).collect(),
+            initial_state: None,
+            current_state: None,
+            states: HashMap::default(),
+            transition_table: HashMap::default(),
+        }
+    }
+
+    pub fn tape_count(&self) -> usize {
+        self.tapes.len()
+    }
+
+    pub fn set_initial_state(&mut self, state_id: ProgramStateId) -> Result<(), TuringError> {
+        if !self.states.contains_key(&state_id) {
+            return Err(TuringError::StateNotDefined(state_id));
+        }
+        self.initial_state = Some(state_id);
+        self.current_state = Some(State::define(state_id));
+        Ok(())
+    }
+
+    pub fn define_states(&mut self, program_states: &[ProgramState]) {
+        program_states.iter().for_each(|state| {
+            self.states.insert(state.id, *state);
+        });
+    }
+
+    pub fn define_transition_table(&mut self, transition_rules: &[MultiTapeRule]) -> Result<(), TuringError> {
+        self.validate_transition_rules(transition_rules)?;
+
+        for rule in transition_rules {
+            self.transition_table
+                .entry(rule.from_state.id)
+                .or_default()
+                .insert(rule.from_symbols.clone(), rule.clone());
+        }
+        Ok(())
+    }
+
+    fn validate_transition_rules(&self, transition_rules: &[MultiTapeRule]) -> Result<(), TuringError> {
+        for (i, rule) in transition_rules.iter().enumerate() {
+            if !self.states.contains_key(&rule.from_state.id) {
+                return Err(TuringError::StateNotDefined(rule.from_state.id));
+            }
+            if let Some(found) = [rule.from_symbols.len(), rule.new_symbols.len(), rule.head_move_dirs.len()]
+                .into_iter()
+                .find(|&len| len != self.tapes.len())
+            {
+                return Err(TuringError::TapeCountMismatch { expected: self.tapes.len(), found });
+            }
+            if let Some(&symbol) = rule.from_symbols.iter().chain(&rule.new_symbols).find(|symbol| symbol.index() > 1) {
+                return Err(TuringError::SymbolNotInAlphabet(symbol));
+            }
+            let is_duplicate = transition_rules[..i]
+                .iter()
+                .any(|other| other.from_state.id == rule.from_state.id && other.from_symbols == rule.from_symbols);
+            if is_duplicate {
+                return Err(TuringError::DuplicateMultiTapeRule {
+                    state: rule.from_state.id,
+                    symbols: rule.from_symbols.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `cells` to the given tape, starting at its head. Fails if one of them is not a binary symbol.
+    pub fn write_to_tape(&mut self, tape: usize, cells: &[Symbol]) -> Result<(), TuringError> {
+        self.tapes[tape].write_to_tape(cells)
+    }
+
+    pub fn head(&self, tape: usize) -> usize {
+        self.tapes[tape].head()
+    }
+
+    pub fn read_tape(&self, tape: usize, start: usize, len: usize) -> Vec<Symbol> {
+        self.tapes[tape].read_tape(start, len)
+    }
+
+    pub fn current_state(&self) -> Option<State> {
+        self.current_state
+    }
+
+    /// The symbols under all heads, in tape order.
+    pub fn head_values(&self) -> Vec<Symbol> {
+        self.tapes.iter().map(TuringMachine::get_head_value).collect()
+    }
+
+    pub fn get_transition_rule(&self, state_id: &ProgramStateId, symbols: &[Symbol]) -> Option<&MultiTapeRule> {
+        self.transition_table
+            .get(state_id)
+            .and_then(|rules| rules.get(symbols))
+    }
+
+    /// Applies a single transition, advancing all heads, and returns the state the machine moved to.
+    pub fn step(&mut self) -> Result<State, TuringError> {
+        let state_id = match self.current_state.ok_or(TuringError::InitialStateNotSet)? {
+            State::ProgramState(ProgramState { id }) => id,
+            state => return Ok(state),
+        };
+
+        let next_state = match self.get_transition_rule(&state_id, &self.head_values()).cloned() {
+            Some(rule) => {
+                for (tape, (symbol, direction)) in self.tapes.iter_mut().zip(rule.new_symbols.iter().zip(rule.head_move_dirs)) {
+                    tape.set_symbol(tape.head, *symbol);
+                    tape.move_head(direction);
+                }
+                rule.to_state
+            }
+            None => State::Halt,
+        };
+
+        self.current_state = Some(next_state);
+        Ok(next_state)
+    }
+
+    pub fn run(&mut self) -> Result<State, TuringError> {
+        loop {
+            let state = self.step()?;
+            if state.is_halting() {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Runs the machine like `run`, writing the state and every tape before each step to `output`.
+    pub fn run_with_output(&mut self, output: &mut dyn Write) -> Result<State, TuringError> {
+        loop {
+            if let Some(State::ProgramState(ProgramState { id })) = self.current_state {
+                let tapes = self.tapes.iter().map(|tape| tape.format_tape(true)).collect::<Vec<_>>();
+                writeln!(output, "q{}: {}", id, tapes.join(" | "))
+                    .map_err(|err| TuringError::Io(format!("failed to write the trace: {}", err)))?;
+            }
+            let state = self.step()?;
+            if state.is_halting() {
+                return Ok(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // copies the first tape onto the second one, then terminates
+    fn copy_machine() -> MultiTapeTuringMachine {
+        let mut machine = MultiTapeTuringMachine::new(2);
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+        machine.set_initial_state(q1.id).unwrap();
+        machine.define_transition_table(&[
+            MultiTapeRule::new(q1, &[Symbol::One, Symbol::Zero], &[Symbol::One, Symbol::One], &[Direction::Right, Direction::Right], State::define(1)),
+            MultiTapeRule::new(q1, &[Symbol::Zero, Symbol::Zero], &[Symbol::Zero, Symbol::Zero], &[Direction::Stay, Direction::Stay], State::Termination),
+        ]).unwrap();
+        machine
+    }
+
+    #[test]
+    fn run_copies_tape_test() {
+        let mut machine = copy_machine();
+        let start = machine.head(1);
+        machine.write_to_tape(0, &Symbol::unary(3)).unwrap();
+
+        assert_eq!(machine.run(), Ok(State::Termination));
+        assert_eq!(machine.read_tape(1, start, 4), Symbol::vec_from_numbers(&[1, 1, 1, 0]));
+        assert_eq!(machine.head(0), start + 3);
+        assert_eq!(machine.head(1), start + 3);
+    }
+
+    #[test]
+    fn compare_tapes_test() {
+        // accepts when both tapes hold unary numbers of the same length
+        let compare = |a: usize, b: usize| {
+            let mut machine = MultiTapeTuringMachine::new(2);
+            let q1 = ProgramState { id: 1 };
+            machine.define_states(&[q1]);
+            machine.set_initial_state(q1.id).unwrap();
+            machine.define_transition_table(&[
+                MultiTapeRule::new(q1, &[Symbol::One, Symbol::One], &[Symbol::One, Symbol::One], &[Direction::Right, Direction::Right], State::define(1)),
+                MultiTapeRule::new(q1, &[Symbol::Zero, Symbol::Zero], &[Symbol::Zero, Symbol::Zero], &[Direction::Stay, Direction::Stay], State::Accept),
+                MultiTapeRule::new(q1, &[Symbol::One, Symbol::Zero], &[Symbol::One, Symbol::Zero], &[Direction::Stay, Direction::Stay], State::Reject),
+                MultiTapeRule::new(q1, &[Symbol::Zero, Symbol::One], &[Symbol::Zero, Symbol::One], &[Direction::Stay, Direction::Stay], State::Reject),
+            ]).unwrap();
+            machine.write_to_tape(0, &Symbol::unary(a)).unwrap();
+            machine.write_to_tape(1, &Symbol::unary(b)).unwrap();
+            machine.run().unwrap()
+        };
+
+        assert_eq!(compare(3, 3), State::Accept);
+        assert_eq!(compare(0, 0), State::Accept);
+        assert_eq!(compare(3, 2), State::Reject);
+        assert_eq!(compare(1, 4), State::Reject);
+    }
+
+    #[test]
+    fn run_with_output_prints_every_tape_test() {
+        let mut machine = copy_machine();
+        machine.write_to_tape(0, &[Symbol::One]).unwrap();
+
+        let mut output = Vec::new();
+        machine.run_with_output(&mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.lines().all(|line| line.starts_with("q1: ") && line.matches(" | ").count() == 1));
+    }
+
+    #[test]
+    fn define_transition_table_validates_rules_test() {
+        let mut machine = MultiTapeTuringMachine::new(2);
+        let q1 = ProgramState { id: 1 };
+        machine.define_states(&[q1]);
+        let rule = MultiTapeRule::new(q1, &[Symbol::One, Symbol::Zero], &[Symbol::One, Symbol::One], &[Direction::Stay, Direction::Stay], State::Halt);
+
+        let short = MultiTapeRule::new(q1, &[Symbol::One], &[Symbol::One], &[Direction::Stay], State::Halt);
+        assert_eq!(machine.define_transition_table(&[short]), Err(TuringError::TapeCountMismatch { expected: 2, found: 1 }));
+        assert_eq!(
+            machine.define_transition_table(&[rule.clone(), rule.clone()]),
+            Err(TuringError::DuplicateMultiTapeRule { state: 1, symbols: vec![Symbol::One, Symbol::Zero] }),
+        );
+
+        // rules that agree on the first tape only are not duplicates, the reported symbols cover every tape
+        let other = MultiTapeRule::new(q1, &[Symbol::One, Symbol::One], &[Symbol::One, Symbol::One], &[Direction::Stay, Direction::Stay], State::Halt);
+        assert!(machine.define_transition_table(&[rule.clone(), other.clone()]).is_ok());
+        let result = machine.define_transition_table(&[other.clone(), rule.clone(), other]);
+        assert_eq!(result, Err(TuringError::DuplicateMultiTapeRule { state: 1, symbols: vec![Symbol::One, Symbol::One] }));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ERROR: State with id `1` is already bound to a transition rule for symbols `(1, 1)` as a `from_state`",
+        );
+        let mut machine = MultiTapeTuringMachine::new(2);
+        machine.define_states(&[q1]);
+        assert_eq!(machine.step(), Err(TuringError::InitialStateNotSet));
+        assert!(machine.define_transition_table(&[rule]).is_ok());
+    }
+}
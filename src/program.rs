@@ -0,0 +1,550 @@
+use crate::{DEFAULT_ALPHABET, MAX_ALPHABET_SIZE, Direction, ProgramState, ProgramStateId, State, Symbol, TransitionRule, TuringError, TuringMachine};
+use std::str::FromStr;
+
+// Text format of a program, one transition rule per line in the `state symbol -> new_symbol direction new_state` notation:
+//
+//     # adds two unary numbers
+//     start: q1
+//     q1 1 -> 0 R q2
+//     q4 0 -> 0 S halt
+//
+// States are written as `q<id>` or just `<id>`, `halt`, `term`, `accept` and `reject` stand for `State::Halt`,
+// `State::Termination`, `State::Accept` and `State::Reject`.
+// Blank lines and everything after a `#` are ignored. Without a `start:` directive the machine starts in the
+// state of the first rule. A `states: q1 q2 ...` directive defines states that no rule mentions.
+// A `*` symbol makes a wildcard rule matching every symbol without a rule of its own, and writes back the symbol read:
+//
+//     q2 * -> * R q2
+//
+// and an `else` symbol a default rule, which only applies if neither an explicit nor a wildcard rule does:
+//
+//     q3 else -> 0 L q1
+//
+// Machines are binary unless an `alphabet: _ a b` directive lists the symbols, the first one being the blank
+// unless a `blank: _` directive names another one. Rules then read and write these symbols. Further directives
+// set what `to_spec` writes out of a machine: `labels: q1=scan q2=done` names states, `accept: q2` marks
+// accepting states and `nondeterministic: true` allows several rules for the same state and symbol.
+
+const START_DIRECTIVE: &str = "start:";
+const STATES_DIRECTIVE: &str = "states:";
+const ALPHABET_DIRECTIVE: &str = "alphabet:";
+const BLANK_DIRECTIVE: &str = "blank:";
+const LABELS_DIRECTIVE: &str = "labels:";
+const ACCEPT_DIRECTIVE: &str = "accept:";
+const NONDETERMINISTIC_DIRECTIVE: &str = "nondeterministic:";
+const WILDCARD: &str = "*";
+const DEFAULT: &str = "else";
+
+// a rule as written, `None` symbols are `*`, the symbols are looked up in the alphabet once it is known
+struct ParsedRule {
+    from_state: ProgramState,
+    is_default: bool,
+    from_symbol: Option<char>,
+    new_symbol: Option<char>,
+    direction: Direction,
+    to_state: State,
+}
+
+fn parse_state_id(token: &str) -> Result<ProgramStateId, String> {
+    token
+        .strip_prefix('q')
+        .unwrap_or(token)
+        .parse::<ProgramStateId>()
+        .map_err(|_| format!("invalid state `{}`", token))
+}
+
+fn parse_target(token: &str) -> Result<State, String> {
+    match token {
+        "halt" => Ok(State::Halt),
+        "term" => Ok(State::Termination),
+        "accept" => Ok(State::Accept),
+        "reject" => Ok(State::Reject),
+        _ => parse_state_id(token).map(State::define),
+    }
+}
+
+fn parse_symbol(token: &str) -> Result<char, String> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("invalid symbol `{}`", token)),
+    }
+}
+
+fn parse_state_ids(tokens: &str) -> Result<Vec<ProgramStateId>, String> {
+    tokens.split_whitespace().map(parse_state_id).collect()
+}
+
+fn parse_label(token: &str) -> Result<(ProgramStateId, String), String> {
+    let (state, label) = token.split_once('=').ok_or(format!("expected a `state=label` pair, found `{}`", token))?;
+    Ok((parse_state_id(state)?, label.to_string()))
+}
+
+// the characters that separate or mark tokens, symbols and labels containing them cannot be written in a program
+fn is_reserved(c: char) -> bool {
+    c.is_whitespace() || c == '#'
+}
+
+fn parse_direction(token: &str) -> Result<Direction, String> {
+    match token {
+        "L" => Ok(Direction::Left),
+        "R" => Ok(Direction::Right),
+        "S" => Ok(Direction::Stay),
+        _ => Err(format!("invalid direction `{}`, expected `L`, `R` or `S`", token)),
+    }
+}
+
+pub(crate) fn format_target(state: State) -> String {
+    match state {
+        State::ProgramState(state) => format!("q{}", state.id),
+        State::Halt => "halt".to_string(),
+        State::Termination => "term".to_string(),
+        State::Accept => "accept".to_string(),
+        State::Reject => "reject".to_string(),
+    }
+}
+
+fn parse_symbol_pattern(token: &str) -> Result<Option<char>, String> {
+    match token {
+        WILDCARD => Ok(None),
+        _ => parse_symbol(token).map(Some),
+    }
+}
+
+fn resolve_symbol(machine: &TuringMachine, line: usize, symbol: Option<char>) -> Result<Option<Symbol>, TuringError> {
+    symbol.map(|c| machine.symbol(c)).transpose().map_err(|err| at_line(line, err))
+}
+
+fn parse_rule(line: &str) -> Result<ParsedRule, String> {
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [_, DEFAULT, "->", WILDCARD, _, _] => Err(format!("`{}` rules need an explicit new symbol", DEFAULT)),
+        [from_state, from_symbol, "->", new_symbol, direction, to_state] => Ok(ParsedRule {
+            from_state: ProgramState { id: parse_state_id(from_state)? },
+            is_default: from_symbol == DEFAULT,
+            from_symbol: if from_symbol == DEFAULT { None } else { parse_symbol_pattern(from_symbol)? },
+            new_symbol: parse_symbol_pattern(new_symbol)?,
+            direction: parse_direction(direction)?,
+            to_state: parse_target(to_state)?,
+        }),
+        _ => Err("expected a rule in the `state symbol -> new_symbol direction new_state` form".to_string()),
+    }
+}
+
+// errors found after parsing, e.g. duplicate rules, reported at the line that caused them
+fn at_line(line: usize, err: TuringError) -> TuringError {
+    TuringError::AtLine { line, error: Box::new(err) }
+}
+
+impl TuringMachine {
+    /// Builds a machine from a program in the `state symbol -> new_symbol direction new_state` text format,
+    /// defining every state mentioned by its rules.
+    pub fn from_program(src: &str) -> Result<TuringMachine, TuringError> {
+        let mut initial_state = None;
+        let mut states = Vec::<ProgramState>::new();
+        let mut parsed_rules = Vec::new();
+        let (mut alphabet, mut blank, mut accept) = (None, None, None);
+        let (mut labels, mut nondeterministic) = (Vec::new(), false);
+
+        for (i, line) in src.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = if let Some(state) = line.strip_prefix(START_DIRECTIVE) {
+                parse_state_id(state.trim()).map(|id| initial_state = Some((i + 1, id)))
+            } else if let Some(ids) = line.strip_prefix(STATES_DIRECTIVE) {
+                parse_state_ids(ids).map(|ids| states.extend(ids.into_iter().map(|id| ProgramState { id })))
+            } else if let Some(symbols) = line.strip_prefix(ALPHABET_DIRECTIVE) {
+                symbols.split_whitespace()
+                    .map(|token| parse_symbol_pattern(token)?.ok_or(format!("`{}` cannot be an alphabet symbol", WILDCARD)))
+                    .collect::<Result<Vec<_>, String>>()
+                    .map(|symbols| alphabet = Some((i + 1, symbols)))
+            } else if let Some(symbol) = line.strip_prefix(BLANK_DIRECTIVE) {
+                parse_symbol(symbol.trim()).map(|c| blank = Some((i + 1, c)))
+            } else if let Some(pairs) = line.strip_prefix(LABELS_DIRECTIVE) {
+                pairs.split_whitespace()
+                    .map(parse_label)
+                    .collect::<Result<Vec<_>, String>>()
+                    .map(|pairs| labels.extend(pairs))
+            } else if let Some(ids) = line.strip_prefix(ACCEPT_DIRECTIVE) {
+                parse_state_ids(ids).map(|ids| accept = Some((i + 1, ids)))
+            } else if let Some(value) = line.strip_prefix(NONDETERMINISTIC_DIRECTIVE) {
+                let value = value.trim();
+                value.parse().map(|value| nondeterministic = value).map_err(|_| format!("expected `true` or `false`, found `{}`", value))
+            } else {
+                parse_rule(line).map(|rule| parsed_rules.push((i + 1, rule)))
+            };
+            parsed.map_err(|message| TuringError::Parse { line: i + 1, message })?;
+        }
+
+        for (_, rule) in &parsed_rules {
+            for state in [State::ProgramState(rule.from_state), rule.to_state] {
+                if let State::ProgramState(state) = state {
+                    if !states.iter().any(|defined| defined.id == state.id) {
+                        states.push(state);
+                    }
+                }
+            }
+        }
+
+        let symbols = alphabet.as_ref().map_or(&DEFAULT_ALPHABET[..], |(_, symbols)| symbols);
+        let blank_symbol = blank.map(|(_, c)| c).or(symbols.first().copied()).unwrap_or_default();
+        let mut machine = TuringMachine::with_alphabet(symbols, blank_symbol).map_err(|err| {
+            let blank_line = blank.filter(|_| matches!(err, TuringError::BlankNotInAlphabet(_))).map(|(line, _)| line);
+            at_line(blank_line.or(alphabet.as_ref().map(|(line, _)| *line)).unwrap(), err)
+        })?;
+        machine.set_nondeterministic(nondeterministic);
+        machine.define_states(&states);
+        let (mut lines, mut rules) = (Vec::new(), Vec::new());
+        for &(line, ref rule) in &parsed_rules {
+            let new_symbol = resolve_symbol(&machine, line, rule.new_symbol)?;
+            match resolve_symbol(&machine, line, rule.from_symbol)? {
+                None if rule.is_default => machine
+                    .define_default_rule(rule.from_state, new_symbol.unwrap(), rule.direction, rule.to_state)
+                    .map_err(|err| at_line(line, err))?,
+                Some(from_symbol) => {
+                    lines.push(line);
+                    rules.push(TransitionRule::new(
+                        rule.from_state,
+                        from_symbol,
+                        new_symbol.unwrap_or(from_symbol),
+                        rule.direction,
+                        rule.to_state,
+                    ));
+                }
+                None => machine
+                    .define_wildcard_rule(rule.from_state, new_symbol, rule.direction, rule.to_state)
+                    .map_err(|err| at_line(line, err))?,
+            }
+        }
+        if let Err(err) = machine.define_transition_table(&rules) {
+            // the rules are validated in order, so the shortest invalid prefix ends at the offending rule
+            let invalid = (1..=rules.len()).find(|&len| machine.validate_transition_rules(&rules[..len]).is_err());
+            return Err(at_line(lines[invalid.unwrap() - 1], err));
+        }
+        let (line, initial_state) = initial_state
+            .or(parsed_rules.first().map(|(line, rule)| (*line, rule.from_state.id)))
+            .ok_or(TuringError::EmptyProgram)?;
+        machine.set_initial_state(initial_state).map_err(|err| at_line(line, err))?;
+        if let Some((line, ids)) = accept {
+            machine.set_accepting_states(&ids).map_err(|err| at_line(line, err))?;
+        }
+        machine.labels.extend(labels);
+
+        Ok(machine)
+    }
+
+    /// Renders the machine definition in the `from_program` text format, with the states and rules
+    /// sorted by state id and symbol so that the output does not depend on `HashMap` iteration order.
+    /// Only the definition is written, not the tape, the head or the current state. Fails with
+    /// `TuringError::InvalidDefinition` if an alphabet symbol or a state label contains whitespace or a `#`,
+    /// or an alphabet symbol is `*`, as these cannot be told apart from the rest of a line.
+    pub fn to_spec(&self) -> Result<String, TuringError> {
+        if let Some(c) = self.alphabet.iter().find(|&&c| is_reserved(c) || c.to_string() == WILDCARD) {
+            return Err(TuringError::InvalidDefinition(format!("alphabet symbol `{}` cannot be written in a program", c)));
+        }
+        if let Some(label) = self.labels.values().find(|label| label.contains(is_reserved)) {
+            return Err(TuringError::InvalidDefinition(format!("state label `{}` cannot be written in a program", label)));
+        }
+        let symbol = |symbol: Symbol| self.symbol_char(symbol).unwrap().to_string();
+        let symbol_pattern = |pattern: Option<Symbol>| pattern.map_or(WILDCARD.to_string(), symbol);
+
+        let mut states = self.states.keys().copied().collect::<Vec<_>>();
+        states.sort();
+        // (state, order within the state, from symbol, new symbol, direction, target), wildcard and default rules
+        // come after the explicit rules of their state, rules for the same state and symbol keep their order
+        let mut rules = self.transition_table
+            .values()
+            .flat_map(|rules| rules.values().flatten())
+            .map(|rule| {
+                let from_symbol = symbol(rule.from_symbol);
+                (rule.from_state.id, rule.from_symbol.index() as usize, from_symbol, Some(rule.new_symbol), rule.head_move_dir, rule.to_state)
+            })
+            .chain(self.wildcard_rules.iter().map(|(id, rule)| {
+                (*id, MAX_ALPHABET_SIZE, WILDCARD.to_string(), rule.new_symbol, rule.head_move_dir, rule.to_state)
+            }))
+            .chain(self.default_rules.iter().map(|(id, rule)| {
+                (*id, MAX_ALPHABET_SIZE + 1, DEFAULT.to_string(), rule.new_symbol, rule.head_move_dir, rule.to_state)
+            }))
+            .collect::<Vec<_>>();
+        rules.sort_by_key(|(id, order, ..)| (*id, *order));
+
+        let mut spec = String::new();
+        if self.alphabet != DEFAULT_ALPHABET || self.blank != Symbol::Zero {
+            let alphabet = self.alphabet.iter().map(char::to_string).collect::<Vec<_>>();
+            spec += &format!("{} {}\n", ALPHABET_DIRECTIVE, alphabet.join(" "));
+            if self.blank != Symbol::Zero {
+                spec += &format!("{} {}\n", BLANK_DIRECTIVE, symbol(self.blank));
+            }
+        }
+        if self.nondeterministic {
+            spec += &format!("{} true\n", NONDETERMINISTIC_DIRECTIVE);
+        }
+        if let Some(initial_state) = self.initial_state {
+            spec += &format!("{} q{}\n", START_DIRECTIVE, initial_state);
+        }
+        if !states.is_empty() {
+            let states = states.iter().map(|id| format!("q{}", id)).collect::<Vec<_>>();
+            spec += &format!("{} {}\n", STATES_DIRECTIVE, states.join(" "));
+        }
+        let mut labels = self.labels.iter().collect::<Vec<_>>();
+        labels.sort();
+        if !labels.is_empty() {
+            let labels = labels.iter().map(|(id, label)| format!("q{}={}", id, label)).collect::<Vec<_>>();
+            spec += &format!("{} {}\n", LABELS_DIRECTIVE, labels.join(" "));
+        }
+        let mut accepting_states = self.accepting_states.iter().collect::<Vec<_>>();
+        accepting_states.sort();
+        if !accepting_states.is_empty() {
+            let accepting_states = accepting_states.iter().map(|id| format!("q{}", id)).collect::<Vec<_>>();
+            spec += &format!("{} {}\n", ACCEPT_DIRECTIVE, accepting_states.join(" "));
+        }
+        for (id, _, from_symbol, new_symbol, head_move_dir, to_state) in rules {
+            spec += &format!(
+                "q{} {} -> {} {} {}\n",
+                id,
+                from_symbol,
+                symbol_pattern(new_symbol),
+                head_move_dir,
+                format_target(to_state),
+            );
+        }
+        Ok(spec)
+    }
+}
+
+impl FromStr for TuringMachine {
+    type Err = TuringError;
+
+    /// Same as `TuringMachine::from_program`, so that programs can be parsed with `src.parse::<TuringMachine>()`.
+    fn from_str(src: &str) -> Result<TuringMachine, TuringError> {
+        TuringMachine::from_program(src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the machine from `main.rs`
+    const EXAMPLE_PROGRAM: &str = "
+        # adds two unary numbers separated by a zero
+        start: q1
+
+        q1 0 -> 0 S term
+        q1 1 -> 0 R q2
+        q2 0 -> 1 L q3
+        q2 1 -> 1 R q2   # scan right over the first number
+        q3 0 -> 0 R q4
+        q3 1 -> 1 L q3
+        q4 0 -> 0 S halt
+        q4 1 -> 0 R term
+    ";
+
+    #[test]
+    fn from_program_runs_example_test() {
+        let mut machine = TuringMachine::from_program(EXAMPLE_PROGRAM).unwrap();
+        machine.write_to_tape(&Symbol::vec_from_str("11110111").unwrap()).unwrap();
+
+        let report = machine.run_reporting().unwrap();
+
+        assert!(matches!(report.final_state, State::Termination));
+        assert_eq!(report.steps, 10);
+        assert_eq!(report.visited_states, vec![1, 2, 2, 2, 2, 3, 3, 3, 3, 4]);
+    }
+
+    #[test]
+    fn from_program_infers_states_and_start_test() {
+        let machine = TuringMachine::from_program("2 0 -> 1 R 7\n7 1 -> 1 L halt").unwrap();
+
+        assert!(matches!(machine.current_state(), Some(State::ProgramState(ProgramState { id: 2 }))));
+        assert!(machine.get_transition_rule(&7, &Symbol::One).is_some());
+    }
+
+    #[test]
+    fn from_program_reports_line_numbers_test() {
+        let errors = [
+            ("q1 0 -> 0 S term\nq1 1 -> 0 X q2", "ERROR: line 2: invalid direction `X`, expected `L`, `R` or `S`"),
+            ("\n\nq1 0 => 0 S term", "ERROR: line 3: expected a rule in the `state symbol -> new_symbol direction new_state` form"),
+            ("# only a comment\nstart: qx", "ERROR: line 2: invalid state `qx`"),
+            ("q1 01 -> 0 S term", "ERROR: line 1: invalid symbol `01`"),
+            ("", "ERROR: program has no rules and no `start:` directive"),
+        ];
+
+        for (src, expected) in errors {
+            assert_eq!(TuringMachine::from_program(src).err().map(|err| err.to_string()), Some(expected.to_string()));
+        }
+        assert_eq!(
+            TuringMachine::from_program("q1 0 -> 0 S term\nq1 1 -> 0 X q2").err(),
+            Some(TuringError::Parse { line: 2, message: "invalid direction `X`, expected `L`, `R` or `S`".to_string() }),
+        );
+        assert_eq!(TuringMachine::from_program("").err(), Some(TuringError::EmptyProgram));
+        assert_eq!(
+            TuringMachine::from_program("start: q3\nq1 0 -> 0 S term").err(),
+            Some(TuringError::AtLine { line: 1, error: Box::new(TuringError::StateNotDefined(3)) }),
+        );
+    }
+
+    #[test]
+    fn from_program_reports_line_numbers_of_invalid_rules_test() {
+        let errors = [
+            (
+                "q1 0 -> 0 S term\n# a comment\nq1 1 -> 1 R q1\nq1 0 -> 1 L halt",
+                "ERROR: line 4: State with id `1` is already bound to a transition rule for symbol `0` as a `from_state`",
+            ),
+            ("q1 0 -> 0 S term\nq1 1 -> 2 R q1", "ERROR: line 2: symbol `2` is not part of the alphabet"),
+            ("q1 0 -> 0 S term\n\nq1 * -> 2 R q1", "ERROR: line 3: symbol `2` is not part of the alphabet"),
+            ("q1 0 -> 0 S term\nq2 else -> 3 R q1", "ERROR: line 2: symbol `3` is not part of the alphabet"),
+            ("q1 0 -> 0 S term\nstart: q2", "ERROR: line 2: state with id `2` is not defined"),
+        ];
+
+        for (src, expected) in errors {
+            assert_eq!(TuringMachine::from_program(src).err().map(|err| err.to_string()), Some(expected.to_string()));
+        }
+
+        // the error found at the line is kept for matching
+        let duplicate = TuringMachine::from_program("q1 0 -> 0 S term\nq1 0 -> 1 L halt").err().unwrap();
+        let duplicate_rule = TuringError::DuplicateRule { state: 1, label: None, symbol: Symbol::Zero };
+        assert_eq!(duplicate, TuringError::AtLine { line: 2, error: Box::new(duplicate_rule.clone()) });
+        assert_eq!(std::error::Error::source(&duplicate).map(ToString::to_string), Some(duplicate_rule.to_string()));
+    }
+
+    #[test]
+    fn parse_runs_like_main_example_test() {
+        let mut parsed = EXAMPLE_PROGRAM.parse::<TuringMachine>().unwrap();
+
+        let mut defined = TuringMachine::new();
+        let [q1, q2, q3, q4] = [1, 2, 3, 4].map(|id| ProgramState { id });
+        defined.define_states(&[q1, q2, q3, q4]);
+        defined.set_initial_state(q1.id).unwrap();
+        defined.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Termination),
+            TransitionRule::new(q1, Symbol::One, Symbol::Zero, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q2, Symbol::Zero, Symbol::One, Direction::Left, State::ProgramState(q3)),
+            TransitionRule::new(q2, Symbol::One, Symbol::One, Direction::Right, State::ProgramState(q2)),
+            TransitionRule::new(q3, Symbol::Zero, Symbol::Zero, Direction::Right, State::ProgramState(q4)),
+            TransitionRule::new(q3, Symbol::One, Symbol::One, Direction::Left, State::ProgramState(q3)),
+            TransitionRule::new(q4, Symbol::Zero, Symbol::Zero, Direction::Stay, State::Halt),
+            TransitionRule::new(q4, Symbol::One, Symbol::Zero, Direction::Right, State::Termination),
+        ]).unwrap();
+
+        let input = Symbol::vec_from_str("11110111").unwrap();
+        parsed.write_to_tape(&input).unwrap();
+        defined.write_to_tape(&input).unwrap();
+
+        let (parsed_state, parsed_trace) = parsed.run_traced().unwrap();
+        let (defined_state, defined_trace) = defined.run_traced().unwrap();
+        assert_eq!(parsed_state, defined_state);
+        assert!(parsed_trace == defined_trace);
+        assert_eq!(parsed.read_observed(), defined.read_observed());
+        assert_eq!("q1 0 -> 0 S nowhere".parse::<TuringMachine>().err(), Some(TuringError::Parse { line: 1, message: "invalid state `nowhere`".to_string() }));
+    }
+
+    #[test]
+    fn to_spec_round_trip_test() {
+        let machine = EXAMPLE_PROGRAM.parse::<TuringMachine>().unwrap();
+        let spec = machine.to_spec().unwrap();
+
+        assert_eq!(spec, "\
+start: q1
+states: q1 q2 q3 q4
+q1 0 -> 0 S term
+q1 1 -> 0 R q2
+q2 0 -> 1 L q3
+q2 1 -> 1 R q2
+q3 0 -> 0 R q4
+q3 1 -> 1 L q3
+q4 0 -> 0 S halt
+q4 1 -> 0 R term
+");
+        let mut reparsed = spec.parse::<TuringMachine>().unwrap();
+        assert_eq!(reparsed.to_spec().unwrap(), spec);
+
+        reparsed.write_to_tape(&Symbol::vec_from_str("11110111").unwrap()).unwrap();
+        assert!(matches!(reparsed.run(), Ok(State::Termination)));
+        assert_eq!(reparsed.read_unary_blocks(), vec![6]);
+
+        let with_unused_state = TuringMachine::from_program("states: q9\nq1 0 -> 0 S halt").unwrap();
+        assert_eq!(with_unused_state.to_spec().unwrap(), "start: q1\nstates: q1 q9\nq1 0 -> 0 S halt\n");
+    }
+
+    #[test]
+    fn to_spec_round_trips_whole_definition_test() {
+        let mut machine = TuringMachine::with_alphabet(&['a', '_', 'b'], '_').unwrap();
+        machine.set_nondeterministic(true);
+        let [q1, q2] = [1, 2].map(|id| ProgramState { id });
+        machine.define_named_states(&[(1, "scan"), (2, "found=b")]);
+        machine.define_transition_table(&[
+            TransitionRule::new(q1, Symbol::new(0), Symbol::new(0), Direction::Right, State::define(1)),
+            TransitionRule::new(q1, Symbol::new(0), Symbol::new(2), Direction::Stay, State::define(2)),
+            TransitionRule::new(q1, Symbol::new(1), Symbol::new(1), Direction::Stay, State::Reject),
+        ]).unwrap();
+        machine.define_wildcard_rule(q2, None, Direction::Left, State::Accept).unwrap();
+        machine.set_initial_state(1).unwrap();
+        machine.set_accepting_states(&[2]).unwrap();
+
+        let spec = machine.to_spec().unwrap();
+        assert_eq!(spec, "\
+alphabet: a _ b
+blank: _
+nondeterministic: true
+start: q1
+states: q1 q2
+labels: q1=scan q2=found=b
+accept: q2
+q1 a -> a R q1
+q1 a -> b S q2
+q1 _ -> _ S reject
+q2 * -> * L accept
+");
+        let mut reparsed = spec.parse::<TuringMachine>().unwrap();
+        assert_eq!(reparsed.to_spec().unwrap(), spec);
+        assert_eq!(reparsed.state_label(2), Some("found=b"));
+        assert!(reparsed.blank() == Symbol::new(1));
+        reparsed.reset_with_input(&[Symbol::new(0)]).unwrap();
+        assert_eq!(reparsed.run_nondeterministic(), Ok(true));
+
+        let errors = [
+            ("alphabet: a b\nq1 0 -> a S halt", "ERROR: line 2: symbol `0` is not part of the alphabet"),
+            ("alphabet: a *\nq1 a -> a S halt", "ERROR: line 1: `*` cannot be an alphabet symbol"),
+            ("alphabet: a b\nblank: c\nq1 a -> a S halt", "ERROR: line 2: blank symbol `c` is not part of the alphabet"),
+            ("q1 0 -> 0 S halt\naccept: q3", "ERROR: line 2: state with id `3` is not defined"),
+            ("labels: q1\nq1 0 -> 0 S halt", "ERROR: line 1: expected a `state=label` pair, found `q1`"),
+            ("nondeterministic: yes\nq1 0 -> 0 S halt", "ERROR: line 1: expected `true` or `false`, found `yes`"),
+        ];
+        for (src, expected) in errors {
+            assert_eq!(TuringMachine::from_program(src).err().map(|err| err.to_string()), Some(expected.to_string()));
+        }
+
+        let mut unwritable = TuringMachine::with_alphabet(&['#', '1'], '#').unwrap();
+        assert!(matches!(unwritable.to_spec(), Err(TuringError::InvalidDefinition(_))));
+        unwritable = TuringMachine::new();
+        unwritable.define_named_states(&[(1, "two words")]);
+        assert!(matches!(unwritable.to_spec(), Err(TuringError::InvalidDefinition(_))));
+    }
+
+    #[test]
+    fn wildcard_rules_round_trip_test() {
+        let src = "q1 * -> * R q1\nq1 0 -> 1 L q2\nq2 * -> 0 S halt\n";
+        let mut machine = src.parse::<TuringMachine>().unwrap();
+
+        assert_eq!(machine.to_spec().unwrap(), "start: q1\nstates: q1 q2\nq1 0 -> 1 L q2\nq1 * -> * R q1\nq2 * -> 0 S halt\n");
+        machine.write_to_tape(&Symbol::vec_from_str("11").unwrap()).unwrap();
+        assert_eq!(machine.run(), Ok(State::Halt));
+        assert_eq!(machine.read_observed(), Symbol::vec_from_str("101").unwrap());
+        assert_eq!(TuringMachine::from_program("q1 0 -> * S q1").unwrap().get_transition_rule(&1, &Symbol::Zero).unwrap().new_symbol, Symbol::Zero);
+    }
+
+    #[test]
+    fn default_rules_round_trip_test() {
+        let src = "start: q1\nstates: q1\nq1 1 -> 1 R q1\nq1 * -> * S halt\nq1 else -> 1 L term\n";
+        let machine = src.parse::<TuringMachine>().unwrap();
+
+        assert_eq!(machine.to_spec().unwrap(), src);
+        assert_eq!(machine.get_transition_rule(&1, &Symbol::Zero).unwrap().to_state, State::Halt);
+        assert_eq!(
+            TuringMachine::from_program("q1 else -> * S halt").err(),
+            Some(TuringError::Parse { line: 1, message: "`else` rules need an explicit new symbol".to_string() }),
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::bit_vec::{BitCell, USIZE_BIT_SIZE, flip_bit, read_bit, write_bit};
+use std::collections::HashMap;
+
+/// Storage of the bits of a tape, addressed by bit like `bit_vec::read_bit` and grouped into `BitCell`s.
+/// Cells are indexed from `0` to `cell_count() - 1`, growing the tape to the left shifts every index.
+pub trait TapeStorage: Clone + Default {
+    /// A tape of `cells` zeroed cells.
+    fn with_cells(cells: usize) -> Self;
+
+    fn cell_count(&self) -> usize;
+
+    fn cell(&self, index: usize) -> BitCell;
+
+    fn get_bit(&self, bit: usize) -> usize;
+
+    fn set_bit(&mut self, bit: usize);
+
+    fn unset_bit(&mut self, bit: usize);
+
+    fn toggle_bit(&mut self, bit: usize) {
+        match self.get_bit(bit) {
+            0 => self.set_bit(bit),
+            _ => self.unset_bit(bit),
+        }
+    }
+
+    /// Adds `cells` zeroed cells in front of the first one.
+    fn prepend_cells(&mut self, cells: usize);
+
+    /// Adds `cells` zeroed cells after the last one.
+    fn append_cells(&mut self, cells: usize);
+
+    /// Indices of the first and the last non-zero cells.
+    fn written_cells(&self) -> Option<(usize, usize)>;
+}
+
+/// The default dense tape, every cell between the outermost positions the head has reached is allocated.
+impl TapeStorage for Vec<BitCell> {
+    fn with_cells(cells: usize) -> Self {
+        vec![BitCell::ZERO; cells]
+    }
+
+    fn cell_count(&self) -> usize {
+        self.len()
+    }
+
+    fn cell(&self, index: usize) -> BitCell {
+        self[index]
+    }
+
+    fn get_bit(&self, bit: usize) -> usize {
+        read_bit(self, bit)
+    }
+
+    fn set_bit(&mut self, bit: usize) {
+        write_bit(self, bit, 1);
+    }
+
+    fn unset_bit(&mut self, bit: usize) {
+        write_bit(self, bit, 0);
+    }
+
+    fn toggle_bit(&mut self, bit: usize) {
+        flip_bit(self, bit);
+    }
+
+    fn prepend_cells(&mut self, cells: usize) {
+        self.splice(0..0, std::iter::repeat_n(BitCell::ZERO, cells));
+    }
+
+    fn append_cells(&mut self, cells: usize) {
+        self.resize(self.len() + cells, BitCell::ZERO);
+    }
+
+    fn written_cells(&self) -> Option<(usize, usize)> {
+        let first = self.iter().position(|cell| !cell.is_zero())?;
+        let last = self.iter().rposition(|cell| !cell.is_zero())?;
+        Some((first, last))
+    }
+}
+
+/// A tape that only stores its non-zero cells, keyed by their index relative to the first cell of the initial tape,
+/// so cells left of it have negative keys. Growing it in either direction allocates nothing, which suits machines
+/// that roam far from where they started.
+#[derive(Clone, Debug, Default)]
+pub struct SparseTape {
+    cells: HashMap<isize, BitCell>,
+    offset: usize, // number of cells that were prepended, i.e. the index of the cell with key 0
+    len: usize,
+}
+
+impl SparseTape {
+    /// Number of cells actually held in memory, which are exactly the non-zero ones.
+    pub fn stored_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn key(&self, index: usize) -> isize {
+        index as isize - self.offset as isize
+    }
+
+    // applies `update` to the cell holding `bit`, dropping the cell once it is zero, and panics like a dense tape
+    // if the cell is beyond the tape
+    fn update_cell(&mut self, bit: usize, update: impl FnOnce(&mut BitCell, usize)) {
+        let index = bit / USIZE_BIT_SIZE;
+        assert!(index < self.len, "cell index {} out of range", index);
+        let key = self.key(index);
+        let cell = self.cells.entry(key).or_default();
+        update(cell, bit % USIZE_BIT_SIZE);
+        if cell.is_zero() {
+            self.cells.remove(&key);
+        }
+    }
+}
+
+impl TapeStorage for SparseTape {
+    fn with_cells(cells: usize) -> Self {
+        SparseTape { cells: HashMap::default(), offset: 0, len: cells }
+    }
+
+    fn cell_count(&self) -> usize {
+        self.len
+    }
+
+    fn cell(&self, index: usize) -> BitCell {
+        assert!(index < self.len, "cell index {} out of range", index);
+        self.cells.get(&self.key(index)).copied().unwrap_or_default()
+    }
+
+    fn get_bit(&self, bit: usize) -> usize {
+        self.cell(bit / USIZE_BIT_SIZE).get(bit % USIZE_BIT_SIZE)
+    }
+
+    fn set_bit(&mut self, bit: usize) {
+        self.update_cell(bit, |cell, index| cell.set(index));
+    }
+
+    fn unset_bit(&mut self, bit: usize) {
+        self.update_cell(bit, |cell, index| cell.clear(index));
+    }
+
+    fn toggle_bit(&mut self, bit: usize) {
+        self.update_cell(bit, |cell, index| cell.toggle(index));
+    }
+
+    fn prepend_cells(&mut self, cells: usize) {
+        self.offset += cells;
+        self.len += cells;
+    }
+
+    fn append_cells(&mut self, cells: usize) {
+        self.len += cells;
+    }
+
+    fn written_cells(&self) -> Option<(usize, usize)> {
+        let first = self.cells.keys().min()?;
+        let last = self.cells.keys().max()?;
+        Some(((first + self.offset as isize) as usize, (last + self.offset as isize) as usize))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // runs the same accesses against both storages
+    fn exercise<T: TapeStorage>() -> T {
+        let mut tape = T::with_cells(2);
+        tape.set_bit(3);
+        tape.set_bit(USIZE_BIT_SIZE);
+        tape.toggle_bit(USIZE_BIT_SIZE + 1);
+        tape.prepend_cells(2);
+        tape.append_cells(1);
+        tape.unset_bit(3 * USIZE_BIT_SIZE);
+        tape.set_bit(0);
+        tape
+    }
+
+    #[test]
+    fn sparse_tape_matches_dense_tape_test() {
+        let dense = exercise::<Vec<BitCell>>();
+        let sparse = exercise::<SparseTape>();
+
+        assert_eq!(sparse.cell_count(), dense.cell_count());
+        assert_eq!((0..dense.cell_count()).map(|i| sparse.cell(i)).collect::<Vec<_>>(), dense);
+        assert_eq!(sparse.written_cells(), dense.written_cells());
+        assert_eq!(dense.written_cells(), Some((0, 3)));
+        assert_eq!(dense[2], BitCell::new(1 << (USIZE_BIT_SIZE - 4)));
+        assert_eq!(sparse.get_bit(3 * USIZE_BIT_SIZE + 1), 1);
+    }
+
+    #[test]
+    fn sparse_tape_drops_zeroed_cells_test() {
+        let mut tape = SparseTape::with_cells(4);
+        tape.set_bit(5);
+        tape.set_bit(6);
+        tape.toggle_bit(2 * USIZE_BIT_SIZE);
+        assert_eq!(tape.stored_cells(), 2);
+
+        tape.unset_bit(5);
+        tape.toggle_bit(2 * USIZE_BIT_SIZE);
+        assert_eq!(tape.stored_cells(), 1);
+        tape.unset_bit(6);
+        assert_eq!(tape.stored_cells(), 0);
+        assert_eq!(tape.written_cells(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn sparse_tape_rejects_writes_past_end_test() {
+        let mut tape = SparseTape::with_cells(2);
+        tape.set_bit(2 * USIZE_BIT_SIZE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dense_tape_rejects_writes_past_end_test() {
+        let mut tape = Vec::<BitCell>::with_cells(2);
+        tape.set_bit(2 * USIZE_BIT_SIZE);
+    }
+}
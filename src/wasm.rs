@@ -0,0 +1,84 @@
+use crate::TuringMachine;
+use crate::program::format_target;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// A binary machine built from a text program (see `TuringMachine::from_program`), exported to JavaScript as
+/// `TuringMachine`. Errors are thrown as their message strings.
+#[wasm_bindgen(js_name = TuringMachine)]
+pub struct WasmTuringMachine {
+    machine: TuringMachine,
+}
+
+#[wasm_bindgen(js_class = TuringMachine)]
+impl WasmTuringMachine {
+    #[wasm_bindgen(constructor)]
+    pub fn new(program: &str) -> Result<WasmTuringMachine, String> {
+        let machine = TuringMachine::from_program(program).map_err(|err| err.to_string())?;
+        Ok(WasmTuringMachine { machine })
+    }
+
+    /// Writes `input`, a string of alphabet symbols, to the tape starting at the head.
+    #[wasm_bindgen(js_name = writeInput)]
+    pub fn write_input(&mut self, input: &str) -> Result<(), String> {
+        let cells = input
+            .chars()
+            .map(|c| self.machine.symbol(c))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        self.machine.write_to_tape(&cells).map_err(|err| err.to_string())
+    }
+
+    /// Applies a single transition and returns whether the machine is still running.
+    pub fn step(&mut self) -> Result<bool, String> {
+        let state = self.machine.step().map_err(|err| err.to_string())?;
+        Ok(!state.is_halting())
+    }
+
+    /// Runs the machine for at most `max_steps` transitions and returns whether it is still running.
+    pub fn run(&mut self, max_steps: u32) -> Result<bool, String> {
+        for _ in 0..max_steps {
+            if !self.step()? {
+                return Ok(false);
+            }
+        }
+        Ok(!self.machine.current_state().is_none_or(|state| state.is_halting()))
+    }
+
+    /// The observed area of the tape with the head in brackets, e.g. `110[1]0`.
+    pub fn tape(&self) -> String {
+        self.machine.to_string()
+    }
+
+    /// The current state in the notation of text programs, e.g. `q1` or `halt`.
+    pub fn state(&self) -> String {
+        self.machine.current_state().map_or(String::new(), format_target)
+    }
+
+    /// Number of transitions applied so far.
+    pub fn steps(&self) -> f64 {
+        self.machine.steps() as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // only the successful paths, turning errors into JavaScript values needs a wasm runtime
+    #[test]
+    fn wasm_machine_runs_program_test() {
+        let mut machine = WasmTuringMachine::new("start: q1\nq1 1 -> 0 R q1\nq1 0 -> 1 S halt").unwrap();
+        machine.write_input("11").unwrap();
+        assert_eq!(machine.tape(), "[1]1");
+        assert_eq!(machine.state(), "q1");
+
+        assert_eq!(machine.step(), Ok(true));
+        assert_eq!(machine.tape(), "[1]");
+        assert_eq!(machine.run(1), Ok(true));
+        assert_eq!(machine.tape(), "[0]");
+        assert_eq!(machine.run(10), Ok(false));
+        assert_eq!(machine.state(), "halt");
+        assert_eq!(machine.tape(), "[1]");
+        assert_eq!(machine.steps(), 3.0);
+    }
+}
@@ -0,0 +1,75 @@
+use std::process::Command;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_turing-machine");
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn run(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(BINARY).args(args).output().unwrap();
+    (output.status.code(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn runs_machine_file_test() {
+    let (code, stdout) = run(&["--quiet", &format!("{}/adder.tm", FIXTURES), &format!("{}/adder_input.txt", FIXTURES)]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "Terminated\n[1]11111\n");
+}
+
+#[test]
+fn traces_inline_input_test() {
+    let (code, stdout) = run(&[&format!("{}/adder.tm", FIXTURES), "1101"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout.lines().next(), Some("q1: [1]101"));
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.ends_with("q4: [1]11\nTerminated\n[1]1\n"), "{}", stdout);
+
+    let (code, stdout) = run(&["--max-steps", "3", "--quiet", &format!("{}/adder.tm", FIXTURES), "1101"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "");
+    assert_eq!(run(&["--max-steps", "many", &format!("{}/adder.tm", FIXTURES), "1101"]).0, Some(1));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn traces_labeled_states_test() {
+    let labeled = std::env::temp_dir().join(format!("turing-machine-cli-{}.json", std::process::id()));
+    std::fs::write(&labeled, r#"{
+        "states": [1],
+        "initial_state": 1,
+        "transitions": [{ "from_state": 1, "from_symbol": "1", "new_symbol": "0", "direction": "R", "to_state": 1 }],
+        "labels": { "1": "erase" }
+    }"#).unwrap();
+    let (code, stdout) = run(&[labeled.to_str().unwrap(), "11"]);
+    let (limited_code, limited_stdout) = run(&["--max-steps", "1", labeled.to_str().unwrap(), "11"]);
+    std::fs::remove_file(&labeled).unwrap();
+
+    assert_eq!(code, Some(2));
+    assert_eq!(stdout, "q_erase: [1]1\nq_erase: [1]\nHaltedNoRule\n[0]\n");
+    assert_eq!(limited_code, Some(1));
+    assert_eq!(limited_stdout, "q_erase: [1]1\n");
+}
+
+#[test]
+fn exit_code_reflects_final_state_test() {
+    let halting = std::env::temp_dir().join(format!("turing-machine-cli-{}.tm", std::process::id()));
+    std::fs::write(&halting, "q1 1 -> 0 R halt\n").unwrap();
+    let (code, stdout) = run(&["--quiet", halting.to_str().unwrap(), &format!("{}/adder_input.txt", FIXTURES)]);
+    std::fs::remove_file(&halting).unwrap();
+    assert_eq!(code, Some(2));
+    assert_eq!(stdout, "Halted\n[1]110111\n");
+
+    let (code, stdout) = run(&[&format!("{}/adder.tm", FIXTURES), "missing.txt"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "");
+    assert_eq!(run(&["one argument"]).0, Some(1));
+}
+
+#[test]
+fn runs_example_without_arguments_test() {
+    let (code, stdout) = run(&[]);
+
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("Machine terminated"));
+}
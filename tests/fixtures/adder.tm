@@ -0,0 +1,10 @@
+# adds two unary numbers separated by a zero
+start: q1
+q1 0 -> 0 S term
+q1 1 -> 0 R q2
+q2 0 -> 1 L q3
+q2 1 -> 1 R q2
+q3 0 -> 0 R q4
+q3 1 -> 1 L q3
+q4 0 -> 0 S halt
+q4 1 -> 0 R term